@@ -0,0 +1,215 @@
+//! Overview of the balances, staking and mining of an account at one block.
+
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use structopt::StructOpt;
+
+use crate::{
+    app::{
+        output::{
+            amounts, AccountInfoOutput, Amount, MinerLedgerOutput, OutputFormat, UnbondedOutput,
+        },
+        tx::Submitter,
+    },
+    runtime::{
+        primitives::{AccountId, AssetId, BlockNumber, Hash},
+        xpallets::{xassets::AssetType, xstaking::LockedType},
+    },
+    units::{format_asset_amount, PCX},
+    utils::{parse_account, AtBlock},
+};
+
+/// Show the PCX balances, staking locks, asset balances, nominations and mining of an account.
+#[derive(Debug, StructOpt)]
+pub struct Account {
+    #[structopt(index = 1, long, parse(try_from_str = parse_account))]
+    who: AccountId,
+    #[structopt(flatten)]
+    at: AtBlock,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NominationOverview {
+    nomination: Amount,
+    /// Dividend claimable with `xstaking claim`.
+    dividend: Amount,
+    unbonded_chunks: Vec<UnbondedOutput>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MiningOverview {
+    #[serde(flatten)]
+    ledger: MinerLedgerOutput,
+    /// Dividend claimable with `xmining_asset claim`.
+    dividend: Amount,
+    /// Dividend paid to the referral on claiming.
+    referral_dividend: Amount,
+    /// Staked PCX still required to claim the dividend.
+    insufficient_stake: Amount,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AccountOutput {
+    block_number: BlockNumber,
+    block_hash: Hash,
+    #[serde(flatten)]
+    account_info: AccountInfoOutput,
+    staking_locks: BTreeMap<LockedType, Amount>,
+    total_staking_locked: Amount,
+    assets: BTreeMap<AssetId, BTreeMap<AssetType, Amount>>,
+    nominations: BTreeMap<AccountId, NominationOverview>,
+    total_staking_dividend: Amount,
+    mining: BTreeMap<AssetId, MiningOverview>,
+}
+
+impl AccountOutput {
+    fn print_text(&self) {
+        let info = &self.account_info;
+        println!(
+            "Account {} at block #{} ({:?})",
+            info.who, self.block_number, self.block_hash
+        );
+        println!(
+            "PCX: free {}, reserved {}, misc frozen {}, fee frozen {}",
+            PCX.format(info.free.0),
+            PCX.format(info.reserved.0),
+            PCX.format(info.misc_frozen.0),
+            PCX.format(info.fee_frozen.0)
+        );
+
+        println!("Staking locks: {}", PCX.format(self.total_staking_locked.0));
+        for (ty, amount) in &self.staking_locks {
+            println!("  {:?}: {}", ty, PCX.format(amount.0));
+        }
+
+        println!("Assets:");
+        for (asset_id, balances) in &self.assets {
+            let balances = balances
+                .iter()
+                .map(|(ty, amount)| {
+                    format!("{:?} {}", ty, format_asset_amount(*asset_id, amount.0))
+                })
+                .collect::<Vec<_>>();
+            println!("  #{}: {}", asset_id, balances.join(", "));
+        }
+
+        println!(
+            "Nominations: {} pending dividend",
+            PCX.format(self.total_staking_dividend.0)
+        );
+        for (nominee, nomination) in &self.nominations {
+            println!(
+                "  {}: nominated {}, dividend {}",
+                nominee,
+                PCX.format(nomination.nomination.0),
+                PCX.format(nomination.dividend.0)
+            );
+            for chunk in &nomination.unbonded_chunks {
+                println!(
+                    "    unbonding {} locked until block {}",
+                    PCX.format(chunk.value.0),
+                    chunk.locked_until
+                );
+            }
+        }
+
+        println!("Mining:");
+        for (asset_id, mining) in &self.mining {
+            println!(
+                "  #{}: weight {} updated at block {}, dividend {}, referral dividend {}",
+                asset_id,
+                mining.ledger.last_mining_weight.0,
+                mining.ledger.last_mining_weight_update,
+                PCX.format(mining.dividend.0),
+                PCX.format(mining.referral_dividend.0)
+            );
+            if mining.insufficient_stake.0 > 0 {
+                println!(
+                    "    {} more staked to claim",
+                    PCX.format(mining.insufficient_stake.0)
+                );
+            }
+        }
+    }
+}
+
+impl Account {
+    pub async fn run(self, submitter: Submitter, output: OutputFormat) -> Result<()> {
+        // All the queries go through the failover RPC.
+        let rpc = submitter.rpc().await?;
+
+        // The block is pinned so that all the queries are at the same block.
+        let at = match self.at.hash(&rpc, submitter.finality()).await? {
+            Some(hash) => hash,
+            None => rpc.best_hash().await?,
+        };
+        let block_number = rpc
+            .header(Some(at))
+            .await?
+            .ok_or_else(|| anyhow!("Header of block {:?} not found", at))?
+            .number;
+        let metadata = rpc.metadata(Some(at)).await?;
+
+        let who = self.who;
+        let account_info = rpc.get_account_info(&metadata, &who, Some(at)).await?;
+        let locks = rpc.get_locks(&metadata, &who, Some(at)).await?;
+        let assets = rpc.get_asset_balance_of(&who, Some(at)).await?;
+        let nominations = rpc.get_nominations_rpc(who.clone(), Some(at)).await?;
+        let staking_dividend = rpc.get_staking_dividend(who.clone(), Some(at)).await?;
+        let miner_ledgers = rpc.get_miner_ledgers_of(&who, Some(at)).await?;
+        let mining_dividend = rpc.get_mining_dividend(who.clone(), Some(at)).await?;
+
+        let account = AccountOutput {
+            block_number,
+            block_hash: at,
+            account_info: AccountInfoOutput::new(who, account_info),
+            total_staking_locked: locks.values().sum::<u128>().into(),
+            staking_locks: amounts(locks),
+            assets: assets
+                .into_iter()
+                .map(|(asset_id, balances)| (asset_id, amounts(balances)))
+                .collect(),
+            total_staking_dividend: staking_dividend.values().sum::<u128>().into(),
+            nominations: nominations
+                .into_iter()
+                .map(|(nominee, ledger)| {
+                    let dividend = staking_dividend.get(&nominee).copied().unwrap_or_default();
+                    let nomination = NominationOverview {
+                        nomination: ledger.nomination.into(),
+                        dividend: dividend.into(),
+                        unbonded_chunks: ledger
+                            .unbonded_chunks
+                            .into_iter()
+                            .map(Into::into)
+                            .collect(),
+                    };
+                    (nominee, nomination)
+                })
+                .collect(),
+            mining: miner_ledgers
+                .into_iter()
+                .map(|(asset_id, ledger)| {
+                    let dividend = mining_dividend.get(&asset_id).cloned().unwrap_or_default();
+                    let mining = MiningOverview {
+                        ledger: ledger.into(),
+                        dividend: dividend.own.into(),
+                        referral_dividend: dividend.other.into(),
+                        insufficient_stake: dividend.insufficient_stake.into(),
+                    };
+                    (asset_id, mining)
+                })
+                .collect(),
+        };
+        output.print(&account, |account| {
+            account.print_text();
+            Ok(())
+        })?;
+
+        Ok(())
+    }
+}
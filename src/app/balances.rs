@@ -1,13 +1,20 @@
 use anyhow::Result;
+use serde::Serialize;
 use structopt::StructOpt;
-use subxt::balances::{LocksStoreExt, TotalIssuanceStoreExt, TransferCallExt, TransferEventExt};
+use subxt::balances::{LocksStoreExt, TotalIssuanceStoreExt, TransferCall};
 
 use crate::{
+    app::{
+        batch::{read_transfers, submit_batch, BatchParams},
+        output::{Amount, BalanceLockOutput, OutputFormat},
+        tx::Submitter,
+    },
     runtime::{
-        primitives::{AccountId, BlockNumber},
-        ChainXSigner,
+        primitives::{AccountId, Balance},
+        ChainXRuntime,
     },
-    utils::{block_hash, build_client, parse_account},
+    units::{parse_pcx, PCX, PCX_ASSET_ID},
+    utils::{parse_account, AtBlock},
 };
 
 /// Balances
@@ -18,9 +25,14 @@ pub enum Balances {
         /// receiver
         #[structopt(index = 1, long, parse(try_from_str = parse_account))]
         dest: AccountId,
-        /// amount
-        #[structopt(index = 2)]
-        value: u128,
+        /// amount, e.g., `1.5PCX`, `1.5` or `150000000` in the raw units
+        #[structopt(index = 2, parse(try_from_str = parse_pcx))]
+        value: Balance,
+    },
+    /// Transfer to the accounts in a CSV or JSON file, in batches of `utility.batch_all`.
+    BatchTransfer {
+        #[structopt(flatten)]
+        batch: BatchParams,
     },
     /// Inspect the balances storage items.
     Storage(Storage),
@@ -32,41 +44,86 @@ pub enum Storage {
     Locks {
         #[structopt(index = 1, long, parse(try_from_str = parse_account))]
         who: AccountId,
-        #[structopt(long)]
-        block_number: Option<BlockNumber>,
+        #[structopt(flatten)]
+        at: AtBlock,
     },
     // Total issuance
     TotalIssuance {
-        #[structopt(long)]
-        block_number: Option<BlockNumber>,
+        #[structopt(flatten)]
+        at: AtBlock,
     },
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LocksOutput {
+    who: AccountId,
+    locks: Vec<BalanceLockOutput>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TotalIssuanceOutput {
+    total_issuance: Amount,
+}
+
 impl Balances {
-    pub async fn run(self, url: String, signer: ChainXSigner) -> Result<()> {
-        let client = build_client(url).await?;
+    pub async fn run(self, submitter: Submitter, output: OutputFormat) -> Result<()> {
+        let client = submitter.client().await?;
 
         match self {
             Balances::Transfer { dest, value } => {
-                let result = client
-                    .transfer_and_watch(&signer, &dest.into(), value)
-                    .await?;
-                if let Some(event) = result.transfer()? {
-                    println!("Balance transfer success: value: {:?}", event.amount);
-                } else {
-                    println!("Failed to find Balances::Transfer Event");
+                let call = TransferCall::<ChainXRuntime> {
+                    to: &dest.into(),
+                    amount: value,
+                };
+                submitter.submit_and_print(&client, call).await?;
+            }
+            Balances::BatchTransfer { batch } => {
+                let transfers = read_transfers(&batch.file, PCX_ASSET_ID)?;
+                let mut calls = Vec::with_capacity(transfers.len());
+                for transfer in &transfers {
+                    calls.push(client.encode(TransferCall::<ChainXRuntime> {
+                        to: &transfer.dest.clone().into(),
+                        amount: transfer.value,
+                    })?);
                 }
+                submit_batch(
+                    &client,
+                    &submitter,
+                    output,
+                    &batch,
+                    PCX_ASSET_ID,
+                    &transfers,
+                    calls,
+                )
+                .await?;
             }
             Balances::Storage(storage) => match storage {
-                Storage::Locks { who, block_number } => {
-                    let at = block_hash(&client, block_number).await?;
+                Storage::Locks { who, at } => {
+                    let rpc = submitter.rpc().await?;
+                    let at = at.hash(&rpc, submitter.finality()).await?;
                     let locks = client.locks(&who, at).await?;
-                    println!("{:?}: {:#?}", who, locks);
+                    let locks_output = LocksOutput {
+                        who: who.clone(),
+                        locks: locks.iter().cloned().map(Into::into).collect(),
+                    };
+                    output.print(&locks_output, |_| {
+                        println!("{:?}: {:#?}", who, locks);
+                        Ok(())
+                    })?;
                 }
-                Storage::TotalIssuance { block_number } => {
-                    let at = block_hash(&client, block_number).await?;
+                Storage::TotalIssuance { at } => {
+                    let rpc = submitter.rpc().await?;
+                    let at = at.hash(&rpc, submitter.finality()).await?;
                     let total_issuance = client.total_issuance(at).await?;
-                    println!("Total issuance: {}", total_issuance);
+                    let total_issuance_output = TotalIssuanceOutput {
+                        total_issuance: total_issuance.into(),
+                    };
+                    output.print(&total_issuance_output, |_| {
+                        println!("Total issuance: {}", PCX.format(total_issuance));
+                        Ok(())
+                    })?;
                 }
             },
         }
@@ -0,0 +1,620 @@
+//! Batch transfers packed into `utility.batch_all` calls.
+//!
+//! The chunks are submitted at once with the consecutive nonces. Each chunk is recorded
+//! with its nonce in a receipt file before it's submitted, and updated once it's included.
+//! An interrupted run is resumed by submitting only the transfers not recorded in the
+//! receipt, the recorded chunks not known to be included are resubmitted with the same
+//! nonces unless the nonces have been used on chain, so no transfer is paid twice.
+
+use std::{
+    collections::BTreeSet,
+    fs,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use codec::Encode;
+use frame_support::weights::Weight;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use sp_core::blake2_256;
+use structopt::StructOpt;
+use subxt::{Encoded, ExtrinsicSuccess};
+
+use crate::{
+    app::{
+        output::{Amount, ExtrinsicOutput, OutputFormat},
+        tx::{read_json, write_json, DispatchFailed, NonceUsed, Submitter},
+    },
+    frame::utility::BatchAllCall,
+    runtime::{
+        primitives::{AccountId, AssetId, Balance, Hash, Index},
+        ChainXClient, ChainXRuntime,
+    },
+    units::{format_asset_amount, parse_asset_amount},
+    utils::parse_account,
+};
+
+/// Options of the batch transfer subcommands.
+#[derive(Debug, StructOpt)]
+pub struct BatchParams {
+    /// The CSV or JSON file of the `(address, amount)` rows.
+    ///
+    /// CSV: one `address,amount` per line, an optional header line and the lines
+    /// starting with `#` are ignored.
+    /// JSON: `[{"address": "5...", "amount": "100"}]` or `[["5...", "100"]]`.
+    /// The amount is in the raw units, or in the asset units like `1.5PCX`.
+    #[structopt(long, parse(from_os_str))]
+    pub file: PathBuf,
+
+    /// Max number of transfers in a batch.
+    ///
+    /// The batch will be smaller if it does not fit in the max extrinsic weight.
+    #[structopt(long, default_value = "100")]
+    pub chunk_size: usize,
+
+    /// The receipt file recording the submitted batches, defaults to `<file>.receipt.json`.
+    ///
+    /// An interrupted run will be resumed from the batches recorded in it.
+    #[structopt(long, parse(from_os_str))]
+    pub receipt: Option<PathBuf>,
+}
+
+/// A transfer row of the batch file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransferRow {
+    pub dest: AccountId,
+    pub value: Balance,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonRow {
+    Object { address: String, amount: JsonAmount },
+    Tuple(String, JsonAmount),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonAmount {
+    Number(u64),
+    String(String),
+}
+
+fn parse_row(asset_id: AssetId, address: &str, amount: &str) -> Result<TransferRow> {
+    let dest = parse_account(address.trim())
+        .map_err(|err| anyhow!("invalid address {}: {}", address, err))?;
+    let value = parse_asset_amount(asset_id, amount)?;
+    Ok(TransferRow { dest, value })
+}
+
+/// Parses the rows in CSV, returns `(line number, row)`.
+fn parse_csv(asset_id: AssetId, content: &str) -> Vec<(usize, Result<TransferRow>)> {
+    content
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .enumerate()
+        .filter(|(index, (_, line))| {
+            // Skip the header line.
+            !(*index == 0 && line.to_lowercase().starts_with("address"))
+        })
+        .map(|(_, (line_number, line))| {
+            let row = match line.split(',').collect::<Vec<_>>().as_slice() {
+                [address, amount] => parse_row(asset_id, address, amount),
+                _ => Err(anyhow!("expected `address,amount`")),
+            };
+            (line_number, row)
+        })
+        .collect()
+}
+
+fn parse_json(asset_id: AssetId, content: &str) -> Result<Vec<(usize, Result<TransferRow>)>> {
+    let rows: Vec<JsonRow> = serde_json::from_str(content)?;
+    Ok(rows
+        .into_iter()
+        .enumerate()
+        .map(|(index, row)| {
+            let (address, amount) = match row {
+                JsonRow::Object { address, amount } | JsonRow::Tuple(address, amount) => {
+                    (address, amount)
+                }
+            };
+            let amount = match amount {
+                JsonAmount::Number(amount) => amount.to_string(),
+                JsonAmount::String(amount) => amount,
+            };
+            (index + 1, parse_row(asset_id, &address, &amount))
+        })
+        .collect())
+}
+
+/// Reads the transfers of `asset_id` from a CSV or JSON file, all the rows are validated.
+pub fn read_transfers(path: &Path, asset_id: AssetId) -> Result<Vec<TransferRow>> {
+    let content = fs::read_to_string(path)?;
+    let is_json = path.extension().and_then(|ext| ext.to_str()) == Some("json");
+    let rows = if is_json {
+        parse_json(asset_id, &content)?
+    } else {
+        parse_csv(asset_id, &content)
+    };
+
+    let mut transfers = Vec::with_capacity(rows.len());
+    let mut errors = Vec::new();
+    for (line_number, row) in rows {
+        match row {
+            Ok(row) => transfers.push(row),
+            Err(err) => errors.push(format!("  row {}: {}", line_number, err)),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(anyhow!(
+            "Invalid rows in {}:\n{}",
+            path.display(),
+            errors.join("\n")
+        ));
+    }
+    if transfers.is_empty() {
+        return Err(anyhow!("No transfers found in {}", path.display()));
+    }
+    Ok(transfers)
+}
+
+/// The receipt of the batch transfers.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Receipt {
+    /// Hash of all the encoded calls, to make sure the receipt matches the input.
+    pub calls_hash: Hash,
+    pub chunks: Vec<ChunkReceipt>,
+}
+
+/// Status of a submitted batch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ChunkStatus {
+    /// Submitted, but not known to be included.
+    Submitted,
+    /// Included and all the transfers succeeded.
+    Included,
+    /// The nonce is used on chain, but the batch was not watched until its inclusion.
+    NonceUsed,
+}
+
+/// The receipt of a submitted batch.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChunkReceipt {
+    /// Index of the first transfer in this batch.
+    pub start: usize,
+    /// Index of the first transfer after this batch.
+    pub end: usize,
+    /// Nonce the batch is signed with.
+    pub nonce: Index,
+    pub status: ChunkStatus,
+    /// The extrinsic and events, only for the `Included` batch.
+    #[serde(flatten)]
+    pub result: Option<ExtrinsicOutput>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchOutput<'a> {
+    asset_id: AssetId,
+    transfers: usize,
+    total: Amount,
+    submitted: usize,
+    dry_run: bool,
+    chunks: &'a [ChunkReceipt],
+}
+
+impl BatchOutput<'_> {
+    fn print_text(&self) {
+        println!("Transfers: {}", self.transfers);
+        println!(
+            "Total: {}",
+            format_asset_amount(self.asset_id, self.total.0)
+        );
+        println!("Submitted: {}", self.submitted);
+    }
+}
+
+/// Updates the chunk at `position` by the result of its batch, returns the failure if any.
+///
+/// A successful `batch_all` has made all the transfers of the chunk, whether or not
+/// `Utility::BatchCompleted` is emitted, so the chunk is kept as included. A failed one is
+/// reverted as a whole and removed, so that its transfers are submitted again.
+fn update_chunk(
+    chunks: &mut Vec<ChunkReceipt>,
+    position: usize,
+    result: &Result<ExtrinsicSuccess<ChainXRuntime>>,
+    gap: &mut Option<Index>,
+) -> Option<String> {
+    let chunk = &mut chunks[position];
+    let err = match result {
+        Ok(_) => {
+            chunk.status = ChunkStatus::Included;
+            return None;
+        }
+        Err(err) => err,
+    };
+    let failure = format!("  [{}, {}): {}", chunk.start, chunk.end, err);
+    if err.downcast_ref::<DispatchFailed>().is_some() {
+        chunks.remove(position);
+    } else if err.downcast_ref::<NonceUsed>().is_some() {
+        chunk.status = ChunkStatus::NonceUsed;
+    } else {
+        // Left as submitted, whose nonce is checked in the next run. Unless it's used, e.g.,
+        // `NonceGap` or the extrinsic rejected by the pool, the greater nonces are blocked.
+        *gap = Some(gap.map_or(chunk.nonce, |gap| gap.min(chunk.nonce)));
+    }
+    Some(failure)
+}
+
+/// Returns the `[start, end)` ranges of the calls not covered by the recorded chunks.
+fn pending_ranges(chunks: &[ChunkReceipt], len: usize) -> Vec<(usize, usize)> {
+    let mut covered = chunks
+        .iter()
+        .map(|chunk| (chunk.start, chunk.end))
+        .collect::<Vec<_>>();
+    covered.sort_unstable();
+
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    for (chunk_start, chunk_end) in covered {
+        if chunk_start > start {
+            ranges.push((start, chunk_start.min(len)));
+        }
+        start = start.max(chunk_end);
+    }
+    if start < len {
+        ranges.push((start, len));
+    }
+    ranges
+}
+
+/// Returns the number of calls from `start` fitting in the max extrinsic weight.
+async fn fit_chunk(
+    client: &ChainXClient,
+    submitter: &Submitter,
+    calls: &[Encoded],
+    max_weight: Weight,
+) -> Result<usize> {
+    let mut size = calls.len();
+    loop {
+        let batch = BatchAllCall::<ChainXRuntime> {
+            _runtime: PhantomData,
+            calls: &calls[..size],
+        };
+        let weight = submitter.query_info(client.encode(batch)?.0).await?.weight;
+        if weight <= max_weight {
+            return Ok(size);
+        }
+        if size == 1 {
+            return Err(anyhow!(
+                "A single transfer exceeds the max extrinsic weight {}",
+                max_weight
+            ));
+        }
+        let fit = (size as u128 * max_weight as u128 / weight as u128) as usize;
+        size = fit.max(1).min(size - 1);
+    }
+}
+
+/// Submits the encoded transfer `calls` in chunks of `utility.batch_all`.
+pub async fn submit_batch(
+    client: &ChainXClient,
+    submitter: &Submitter,
+    output: OutputFormat,
+    params: &BatchParams,
+    asset_id: AssetId,
+    transfers: &[TransferRow],
+    calls: Vec<Encoded>,
+) -> Result<()> {
+    if params.chunk_size == 0 {
+        return Err(anyhow!("--chunk-size must be greater than 0"));
+    }
+    let total = transfers
+        .iter()
+        .try_fold(0u128, |total, transfer| total.checked_add(transfer.value))
+        .ok_or_else(|| anyhow!("Total amount overflows"))?;
+
+    let receipt_path = params
+        .receipt
+        .clone()
+        .unwrap_or_else(|| params.file.with_extension("receipt.json"));
+    let calls_hash = Hash::from(blake2_256(&calls.encode()));
+    let mut receipt = if receipt_path.exists() {
+        let receipt: Receipt = read_json(&receipt_path)?;
+        if receipt.calls_hash != calls_hash {
+            return Err(anyhow!(
+                "Receipt {} does not match the transfers in {}",
+                receipt_path.display(),
+                params.file.display()
+            ));
+        }
+        receipt
+    } else {
+        Receipt {
+            calls_hash,
+            chunks: Vec::new(),
+        }
+    };
+    let offline = submitter.is_dry_run() || submitter.is_unsigned_only();
+
+    // The chunks recorded but not known to be included are resubmitted with their nonces,
+    // unless the nonces have been used on chain, i.e., they have been included.
+    let mut submitting = Vec::new();
+    if !offline {
+        for chunk in &mut receipt.chunks {
+            if chunk.status != ChunkStatus::Submitted {
+                continue;
+            }
+            if submitter.is_nonce_used(chunk.nonce).await? {
+                eprintln!(
+                    "Batch of transfers [{}, {}) with nonce {} has been included, but its result \
+                     is not recorded, check it on chain",
+                    chunk.start, chunk.end, chunk.nonce
+                );
+                chunk.status = ChunkStatus::NonceUsed;
+            } else {
+                submitting.push(chunk.nonce);
+            }
+        }
+        write_json(&receipt_path, &receipt)?;
+    }
+    let pending = pending_ranges(&receipt.chunks, calls.len());
+
+    let mut failures = Vec::new();
+    let mut ranges = Vec::new();
+    if !pending.is_empty() {
+        let metadata = submitter.rpc().await?.metadata(None).await?;
+        let max_weight: Weight = metadata.constant("System", "MaximumExtrinsicWeight")?;
+
+        for (mut start, pending_end) in pending {
+            while start < pending_end {
+                let end = (start + params.chunk_size).min(pending_end);
+                let end =
+                    start + fit_chunk(client, submitter, &calls[start..end], max_weight).await?;
+                ranges.push((start, end));
+                start = end;
+            }
+        }
+    }
+
+    if offline {
+        // Only the first batch is written with `--unsigned-only` or dry run with `--dry-run`.
+        if let Some(&(start, end)) = ranges.first() {
+            submitter
+                .submit(
+                    client,
+                    BatchAllCall::<ChainXRuntime> {
+                        _runtime: PhantomData,
+                        calls: &calls[start..end],
+                    },
+                )
+                .await?;
+        }
+        if submitter.is_unsigned_only() {
+            return Ok(());
+        }
+    } else if !ranges.is_empty() || !submitting.is_empty() {
+        // The new batches take the nonces after all the recorded ones.
+        if let Some(max_nonce) = receipt.chunks.iter().map(|chunk| chunk.nonce).max() {
+            submitter.skip_nonces_to(max_nonce + 1).await?;
+        }
+        for (start, end) in ranges {
+            let nonce = submitter.next_nonce().await?;
+            receipt.chunks.push(ChunkReceipt {
+                start,
+                end,
+                nonce,
+                status: ChunkStatus::Submitted,
+                result: None,
+            });
+            submitting.push(nonce);
+        }
+        receipt.chunks.sort_by_key(|chunk| chunk.start);
+        // Recorded before the submission, so that a batch is never submitted again with
+        // another nonce even if this run is interrupted.
+        write_json(&receipt_path, &receipt)?;
+
+        let batches = submitting
+            .iter()
+            .map(|&nonce| {
+                let chunk = receipt
+                    .chunks
+                    .iter()
+                    .find(|chunk| chunk.nonce == nonce)
+                    .expect("Submitting chunks are recorded; qed");
+                let batch = client.encode(BatchAllCall::<ChainXRuntime> {
+                    _runtime: PhantomData,
+                    calls: &calls[chunk.start..chunk.end],
+                })?;
+                Ok((nonce, batch))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let mut in_flight = submitting.iter().copied().collect::<BTreeSet<_>>();
+        // The lowest nonce which is not included even after the resubmissions, the batches
+        // with the greater nonces are blocked by it.
+        let mut gap: Option<Index> = None;
+
+        let mut included = submitter.submit_all(batches);
+        // Drains all the batches except the ones blocked by the nonce gap.
+        while in_flight
+            .iter()
+            .next()
+            .map_or(false, |&nonce| gap.map_or(true, |gap| nonce < gap))
+        {
+            let (index, result) = match included.next().await {
+                Some(item) => item,
+                None => break,
+            };
+            let nonce = submitting[index];
+            in_flight.remove(&nonce);
+            let position = receipt
+                .chunks
+                .iter()
+                .position(|chunk| chunk.nonce == nonce)
+                .expect("Submitting chunks are recorded; qed");
+            failures.extend(update_chunk(
+                &mut receipt.chunks,
+                position,
+                &result,
+                &mut gap,
+            ));
+            if let Ok(result) = &result {
+                let chunk = &mut receipt.chunks[position];
+                if output == OutputFormat::Text {
+                    println!(
+                        "Batch of transfers [{}, {}) is included in block {:?}",
+                        chunk.start, chunk.end, result.block
+                    );
+                }
+                match submitter.extrinsic_output(result).await {
+                    Ok(extrinsic) => chunk.result = Some(extrinsic),
+                    Err(err) => failures.push(format!(
+                        "  [{}, {}): included in block {:?}, but {}",
+                        chunk.start, chunk.end, result.block, err
+                    )),
+                }
+            }
+            write_json(&receipt_path, &receipt)?;
+        }
+        drop(included);
+
+        for nonce in in_flight {
+            if let Some(chunk) = receipt.chunks.iter().find(|chunk| chunk.nonce == nonce) {
+                failures.push(format!(
+                    "  [{}, {}): blocked by the nonce gap at {}",
+                    chunk.start,
+                    chunk.end,
+                    gap.unwrap_or_default()
+                ));
+            }
+        }
+    }
+
+    let batch_output = BatchOutput {
+        asset_id,
+        transfers: transfers.len(),
+        total: total.into(),
+        submitted: receipt
+            .chunks
+            .iter()
+            .filter(|chunk| chunk.status != ChunkStatus::Submitted)
+            .map(|chunk| chunk.end - chunk.start)
+            .sum(),
+        dry_run: submitter.is_dry_run(),
+        chunks: &receipt.chunks,
+    };
+    output.print(&batch_output, |batch_output| {
+        batch_output.print_text();
+        if !submitter.is_dry_run() {
+            println!("Receipt: {}", receipt_path.display());
+        }
+        Ok(())
+    })?;
+
+    if !failures.is_empty() {
+        return Err(anyhow!(
+            "{} batches failed or are not confirmed, run again to resubmit the ones not \
+             included:\n{}",
+            failures.len(),
+            failures.join("\n")
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv() {
+        let content = "address,amount\n# comment\n\nalice,100\nbob, 2PCX\ninvalid,1\nbob\n";
+        let rows = parse_csv(0, content);
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[0].0, 4);
+        assert_eq!(rows[1].1.as_ref().unwrap().value, 200_000_000);
+        assert!(rows[2].1.is_err());
+        assert!(rows[3].1.is_err());
+    }
+
+    #[test]
+    fn test_pending_ranges() {
+        let chunk = |start, end| ChunkReceipt {
+            start,
+            end,
+            nonce: 0,
+            status: ChunkStatus::Submitted,
+            result: None,
+        };
+        assert_eq!(pending_ranges(&[], 10), vec![(0, 10)]);
+        assert_eq!(
+            pending_ranges(&[chunk(6, 8), chunk(0, 3)], 10),
+            vec![(3, 6), (8, 10)]
+        );
+        assert_eq!(pending_ranges(&[chunk(0, 5), chunk(5, 10)], 10), vec![]);
+    }
+
+    #[test]
+    fn test_chunk_receipt_without_result() {
+        let chunk = ChunkReceipt {
+            start: 0,
+            end: 2,
+            nonce: 5,
+            status: ChunkStatus::Submitted,
+            result: None,
+        };
+        let json = serde_json::to_string(&chunk).unwrap();
+        assert_eq!(
+            json,
+            r#"{"start":0,"end":2,"nonce":5,"status":"submitted"}"#
+        );
+        let chunk: ChunkReceipt = serde_json::from_str(&json).unwrap();
+        assert_eq!(chunk.status, ChunkStatus::Submitted);
+        assert!(chunk.result.is_none());
+    }
+
+    #[test]
+    fn test_update_chunk_without_batch_completed() {
+        let chunk = |start, end, nonce| ChunkReceipt {
+            start,
+            end,
+            nonce,
+            status: ChunkStatus::Submitted,
+            result: None,
+        };
+        let mut chunks = vec![chunk(0, 2, 5), chunk(2, 4, 6)];
+        let mut gap = None;
+
+        // `batch_all` succeeded without emitting `Utility::BatchCompleted`.
+        let success = Ok(ExtrinsicSuccess {
+            block: Hash::default(),
+            extrinsic: Hash::default(),
+            events: Vec::new(),
+        });
+        assert!(update_chunk(&mut chunks, 0, &success, &mut gap).is_none());
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].status, ChunkStatus::Included);
+
+        let failed = Err(DispatchFailed("Balances::InsufficientBalance".into()).into());
+        assert!(update_chunk(&mut chunks, 1, &failed, &mut gap).is_some());
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].nonce, 5);
+        assert!(gap.is_none());
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let content = r#"[{"address": "alice", "amount": 100}, ["bob", "200"]]"#;
+        let rows = parse_json(0, content).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].1.as_ref().unwrap().value, 100);
+        assert_eq!(rows[1].1.as_ref().unwrap().value, 200);
+    }
+}
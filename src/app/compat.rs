@@ -0,0 +1,50 @@
+//! Report of the compatibility between `chainx-cli` and the runtime of the node.
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use structopt::StructOpt;
+
+use crate::{
+    app::output::OutputFormat,
+    metadata::compat::{check_compat, CompatReport, Severity},
+    rpc::Rpc,
+    runtime::PALLETS,
+};
+
+/// Check the calls and storages hard-coded in chainx-cli against the runtime metadata.
+///
+/// Returns an error if any of them will be mis-encoded or mis-decoded.
+#[derive(Debug, StructOpt)]
+pub struct CheckCompat {}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CompatOutput {
+    spec_name: String,
+    spec_version: u32,
+    #[serde(flatten)]
+    report: CompatReport,
+}
+
+impl CheckCompat {
+    pub async fn run(self, urls: Vec<String>, output: OutputFormat) -> Result<()> {
+        let rpc = Rpc::new(&urls).await?;
+        let version = rpc.runtime_version(None).await?;
+        let metadata = rpc.metadata(None).await?;
+        let compat = CompatOutput {
+            spec_name: version.spec_name.to_string(),
+            spec_version: version.spec_version,
+            report: check_compat(&metadata, PALLETS),
+        };
+        output.print(&compat, |compat| {
+            println!("Runtime: {} {}", compat.spec_name, compat.spec_version);
+            compat.report.print_text();
+            Ok(())
+        })?;
+
+        match compat.report.count(Severity::Error) {
+            0 => Ok(()),
+            errors => Err(anyhow!("{} incompatible calls or storages found", errors)),
+        }
+    }
+}
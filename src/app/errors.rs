@@ -0,0 +1,44 @@
+//! Decoding of the dispatch errors into the error names and documentation in metadata.
+
+use sp_runtime::DispatchError;
+use subxt::RuntimeError;
+
+use crate::metadata::{ErrorMetadata, Metadata, ModuleMetadata};
+
+fn describe_error(module: &ModuleMetadata, error: &ErrorMetadata) -> String {
+    let documentation = error
+        .documentation
+        .iter()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    if documentation.is_empty() {
+        format!("{}::{}", module.name, error.name)
+    } else {
+        format!("{}::{}: {}", module.name, error.name, documentation)
+    }
+}
+
+/// Describes the dispatch error, e.g., `XStaking::InsufficientBalance: <documentation>`.
+pub fn describe_dispatch_error(metadata: &Metadata, error: DispatchError) -> String {
+    match error {
+        DispatchError::Module { index, error, .. } => match metadata.module_error(index, error) {
+            Ok((module, error)) => describe_error(module, error),
+            Err(_) => format!("Module {{ index: {}, error: {} }}", index, error),
+        },
+        error => format!("{:?}", error),
+    }
+}
+
+/// Describes the error returned by subxt when the extrinsic fails, which has been
+/// decoded into the module and error names.
+pub fn describe_runtime_error(metadata: &Metadata, error: &RuntimeError) -> String {
+    match error {
+        RuntimeError::Module(err) => match metadata.error_by_name(&err.module, &err.error) {
+            Ok((module, error)) => describe_error(module, error),
+            Err(_) => format!("{}::{}", err.module, err.error),
+        },
+        error => format!("{:?}", error),
+    }
+}
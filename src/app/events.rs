@@ -0,0 +1,194 @@
+//! Decoding of the events emitted by an extrinsic.
+//!
+//! The known events are decoded into named arguments, the others are kept as the raw bytes.
+
+use std::fmt::Display;
+
+use anyhow::{anyhow, Result};
+use codec::Decode;
+use frame_support::weights::DispatchInfo;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use sp_runtime::{DispatchError, DispatchResult};
+use subxt::{balances::TransferEvent, Event, RawEvent};
+
+use crate::{
+    app::errors::describe_dispatch_error,
+    metadata::Metadata,
+    runtime::{
+        primitives::{AccountId, AssetId, Balance},
+        xpallets::{
+            xassets::TransferEvent as AssetTransferEvent,
+            xmining_asset::ClaimEvent as MiningClaimEvent,
+            xstaking::{
+                BondedEvent, ChilledEvent, ClaimedEvent, RebondedEvent, RegisteredEvent,
+                UnbondedEvent, WithdrawnEvent,
+            },
+        },
+        ChainXRuntime,
+    },
+    units::{format_asset_amount, PCX},
+};
+
+/// The decoded arguments of an event.
+#[derive(Clone, Debug, Default)]
+pub struct DecodedEvent {
+    /// Arguments for the JSON output, the balances are in the raw units.
+    pub args: Map<String, Value>,
+    /// Arguments for the text output, the balances are formatted with the units.
+    pub text: Vec<String>,
+    /// The error if the event reports a failed dispatch, e.g., `System::ExtrinsicFailed`.
+    pub error: Option<String>,
+}
+
+impl DecodedEvent {
+    fn arg<V: Serialize, T: Display>(mut self, name: &str, value: V, text: T) -> Self {
+        self.args
+            .insert(name.into(), serde_json::to_value(value).unwrap_or_default());
+        self.text.push(format!("{}: {}", name, text));
+        self
+    }
+
+    fn account(self, name: &str, who: &AccountId) -> Self {
+        self.arg(name, who, who)
+    }
+
+    fn balance(self, name: &str, value: Balance) -> Self {
+        self.arg(name, value.to_string(), PCX.format(value))
+    }
+
+    fn asset_balance(self, name: &str, asset_id: AssetId, value: Balance) -> Self {
+        self.arg(
+            name,
+            value.to_string(),
+            format_asset_amount(asset_id, value),
+        )
+    }
+
+    fn dispatch_result(mut self, metadata: &Metadata, result: DispatchResult) -> Self {
+        match result {
+            Ok(()) => self.arg("result", "Ok", "Ok"),
+            Err(err) => {
+                let err = describe_dispatch_error(metadata, err);
+                self.error = Some(err.clone());
+                self.arg("result", format!("Err({})", err), format!("Err({})", err))
+            }
+        }
+    }
+}
+
+/// Decodes the whole input, the trailing bytes mean the event is not the expected one.
+fn decode_exact<T: Decode>(data: &[u8]) -> Result<T> {
+    let mut input = data;
+    let value = T::decode(&mut input)?;
+    if !input.is_empty() {
+        return Err(anyhow!("{} trailing bytes of the event", input.len()));
+    }
+    Ok(value)
+}
+
+fn is<E: Event<ChainXRuntime>>(event: &RawEvent) -> bool {
+    event.module == E::MODULE && event.variant == E::EVENT
+}
+
+/// Decodes the known events, returns `None` for the others.
+pub fn decode_event(metadata: &Metadata, event: &RawEvent) -> Result<Option<DecodedEvent>> {
+    let data = event.data.as_slice();
+    let decoded = DecodedEvent::default();
+    let decoded = match (event.module.as_str(), event.variant.as_str()) {
+        ("System", "ExtrinsicSuccess") => {
+            let info: DispatchInfo = decode_exact(data)?;
+            decoded.arg("weight", info.weight, info.weight).arg(
+                "class",
+                format!("{:?}", info.class),
+                format!("{:?}", info.class),
+            )
+        }
+        ("System", "ExtrinsicFailed") => {
+            let (error, info): (DispatchError, DispatchInfo) = decode_exact(data)?;
+            decoded
+                .dispatch_result(metadata, Err(error))
+                .arg("weight", info.weight, info.weight)
+        }
+        ("System", "NewAccount") | ("System", "KilledAccount") => {
+            decoded.account("account", &decode_exact(data)?)
+        }
+        ("Sudo", "Sudid") => decoded.dispatch_result(metadata, decode_exact(data)?),
+        ("Sudo", "KeyChanged") => decoded.account("new_sudoer", &decode_exact(data)?),
+        ("Utility", "BatchCompleted") => decoded,
+        ("Utility", "BatchInterrupted") => {
+            let (index, error): (u32, DispatchError) = decode_exact(data)?;
+            decoded
+                .arg("index", index, index)
+                .dispatch_result(metadata, Err(error))
+        }
+        _ if is::<TransferEvent<ChainXRuntime>>(event) => {
+            let event: TransferEvent<ChainXRuntime> = decode_exact(data)?;
+            decoded
+                .account("from", &event.from)
+                .account("to", &event.to)
+                .balance("amount", event.amount)
+        }
+        _ if is::<AssetTransferEvent<ChainXRuntime>>(event) => {
+            let event: AssetTransferEvent<ChainXRuntime> = decode_exact(data)?;
+            decoded
+                .account("from", &event.from)
+                .arg("from_type", event.from_type, event.from_type)
+                .account("to", &event.to)
+                .arg("to_type", event.to_type, event.to_type)
+                .asset_balance("amount", event.to_type, event.amount)
+        }
+        _ if is::<MiningClaimEvent<ChainXRuntime>>(event) => {
+            let event: MiningClaimEvent<ChainXRuntime> = decode_exact(data)?;
+            decoded
+                .account("claimer", &event.claimer)
+                .arg("asset_id", event.asset_id, event.asset_id)
+                .balance("amount", event.amount)
+        }
+        _ if is::<RegisteredEvent<ChainXRuntime>>(event) => {
+            let event: RegisteredEvent<ChainXRuntime> = decode_exact(data)?;
+            decoded.account("validator", &event.validator)
+        }
+        _ if is::<ChilledEvent<ChainXRuntime>>(event) => {
+            let event: ChilledEvent<ChainXRuntime> = decode_exact(data)?;
+            decoded.account("validator", &event.validator)
+        }
+        _ if is::<BondedEvent<ChainXRuntime>>(event) => {
+            let event: BondedEvent<ChainXRuntime> = decode_exact(data)?;
+            decoded
+                .account("nominator", &event.nominator)
+                .account("validator", &event.validator)
+                .balance("amount", event.amount)
+        }
+        _ if is::<RebondedEvent<ChainXRuntime>>(event) => {
+            let event: RebondedEvent<ChainXRuntime> = decode_exact(data)?;
+            decoded
+                .account("nominator", &event.nominator)
+                .account("from", &event.from)
+                .account("to", &event.to)
+                .balance("amount", event.amount)
+        }
+        _ if is::<UnbondedEvent<ChainXRuntime>>(event) => {
+            let event: UnbondedEvent<ChainXRuntime> = decode_exact(data)?;
+            decoded
+                .account("nominator", &event.nominator)
+                .account("validator", &event.validator)
+                .balance("amount", event.amount)
+        }
+        _ if is::<ClaimedEvent<ChainXRuntime>>(event) => {
+            let event: ClaimedEvent<ChainXRuntime> = decode_exact(data)?;
+            decoded
+                .account("nominator", &event.nominator)
+                .account("validator", &event.validator)
+                .balance("dividend", event.dividend)
+        }
+        _ if is::<WithdrawnEvent<ChainXRuntime>>(event) => {
+            let event: WithdrawnEvent<ChainXRuntime> = decode_exact(data)?;
+            decoded
+                .account("nominator", &event.nominator)
+                .balance("amount", event.amount)
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(decoded))
+}
@@ -0,0 +1,135 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use structopt::StructOpt;
+
+use crate::{
+    app::output::OutputFormat,
+    keystore::{KeyFile, Keystore as LocalKeystore},
+    runtime::{primitives::AccountId, ChainXSigner, CryptoScheme},
+};
+
+/// Manage the accounts in the local keystore.
+#[derive(Debug, StructOpt)]
+pub enum Keystore {
+    /// Import an account from the encrypted JSON exported by polkadot.js.
+    ImportJson {
+        /// The JSON file exported by polkadot.js.
+        #[structopt(index = 1, parse(from_os_str))]
+        file: PathBuf,
+        /// Name of the account in keystore, defaults to the name in the JSON file.
+        #[structopt(long)]
+        name: Option<String>,
+    },
+    /// Import an account from a secret URI, e.g., `<mnemonic>//hard/soft///password`.
+    ///
+    /// The secret URI is read from the prompt instead of the command line, so that
+    /// it won't be left in the shell history. The key pair is of the global `--scheme`.
+    ImportUri {
+        /// Name of the account in keystore.
+        #[structopt(long)]
+        name: String,
+    },
+    /// List all the accounts in keystore.
+    List,
+    /// Remove an account from keystore.
+    Remove {
+        #[structopt(index = 1)]
+        name: String,
+    },
+}
+
+#[derive(Debug, Serialize)]
+struct KeystoreAccountOutput {
+    name: String,
+    address: AccountId,
+    scheme: CryptoScheme,
+}
+
+impl KeystoreAccountOutput {
+    fn new(name: String, key_file: &KeyFile) -> Result<Self> {
+        Ok(Self {
+            name,
+            address: key_file.account_id()?,
+            scheme: key_file.scheme()?,
+        })
+    }
+}
+
+/// Reads the password from the terminal without echoing.
+pub fn read_password(prompt: &str) -> Result<String> {
+    Ok(rpassword::read_password_from_tty(Some(prompt))?)
+}
+
+fn read_new_password() -> Result<String> {
+    let password = read_password("New password: ")?;
+    if password != read_password("Repeat password: ")? {
+        return Err(anyhow!("Passwords do not match"));
+    }
+    Ok(password)
+}
+
+/// Decrypts the account `name` in keystore, the password is read from the prompt.
+pub fn load_signer(keystore_path: &Path, name: &str) -> Result<ChainXSigner> {
+    let key_file = LocalKeystore::open(keystore_path)?.get(name)?;
+    key_file.decrypt(&read_password(&format!("Password of {}: ", name))?)
+}
+
+impl Keystore {
+    pub fn run(
+        self,
+        keystore_path: &Path,
+        scheme: CryptoScheme,
+        output: OutputFormat,
+    ) -> Result<()> {
+        let keystore = LocalKeystore::open(keystore_path)?;
+        match self {
+            Self::ImportJson { file, name } => {
+                let mut key_file: KeyFile = serde_json::from_reader(std::fs::File::open(file)?)?;
+                let name = name
+                    .or_else(|| key_file.name().map(Into::into))
+                    .ok_or_else(|| anyhow!("Missing the account name, please specify --name"))?;
+                // Make sure the password is known before importing it.
+                key_file.decrypt(&read_password("Password of the JSON file: ")?)?;
+                key_file.set_name(&name);
+                keystore.insert(&name, &key_file)?;
+                let account = KeystoreAccountOutput::new(name, &key_file)?;
+                output.print(&account, |account| {
+                    println!("Imported {}: {}", account.name, account.address);
+                    Ok(())
+                })?;
+            }
+            Self::ImportUri { name } => {
+                let uri = read_password("Secret URI: ")?;
+                let key_file = KeyFile::encrypt(scheme, &uri, &name, &read_new_password()?)?;
+                keystore.insert(&name, &key_file)?;
+                let account = KeystoreAccountOutput::new(name, &key_file)?;
+                output.print(&account, |account| {
+                    println!("Imported {}: {}", account.name, account.address);
+                    Ok(())
+                })?;
+            }
+            Self::List => {
+                let accounts = keystore
+                    .list()?
+                    .into_iter()
+                    .map(|(name, key_file)| KeystoreAccountOutput::new(name, &key_file))
+                    .collect::<Result<Vec<_>>>()?;
+                output.print(&accounts, |accounts| {
+                    for account in accounts {
+                        println!("{}: {} ({})", account.name, account.address, account.scheme);
+                    }
+                    Ok(())
+                })?;
+            }
+            Self::Remove { name } => {
+                keystore.remove(&name)?;
+                if output == OutputFormat::Text {
+                    println!("Removed {}", name);
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,25 +1,50 @@
+pub mod account;
 pub mod balances;
+pub mod batch;
+pub mod compat;
+pub mod errors;
+pub mod events;
+pub mod keystore;
+pub mod nonce;
+pub mod output;
+pub mod runtime;
 pub mod session;
+pub mod storage;
 pub mod sudo;
 pub mod system;
+pub mod tx;
 pub mod xassets;
 pub mod xmining_asset;
 pub mod xstaking;
 
-use anyhow::{anyhow, Result};
-use sp_core::Pair;
+use std::path::PathBuf;
+
+use anyhow::Result;
 use sp_keyring::AccountKeyring;
 use structopt::{clap::arg_enum, StructOpt};
-use subxt::PairSigner;
 
-use crate::runtime::ChainXSigner;
+use crate::{
+    keystore::Keystore,
+    metadata::compat::CompatCheck,
+    runtime::{ChainXSigner, CryptoScheme},
+    utils::Finality,
+};
+
+use self::{
+    output::OutputFormat,
+    tx::{Submitter, TxParams},
+};
 
 #[derive(StructOpt, Debug)]
 pub enum Cmd {
+    Account(account::Account),
     Balances(balances::Balances),
+    Keystore(keystore::Keystore),
+    Runtime(runtime::Runtime),
     Session(session::Session),
     Sudo(sudo::Sudo),
     System(system::System),
+    Storage(storage::Storage),
 
     #[structopt(name = "xassets")]
     XAssets(xassets::XAssets),
@@ -28,6 +53,13 @@ pub enum Cmd {
     #[structopt(name = "xstaking")]
     XStaking(xstaking::XStaking),
 
+    /// Sign an unsigned transaction offline.
+    Sign(tx::Sign),
+    /// Broadcast a signed transaction.
+    Submit(tx::Submit),
+    /// Check the calls and storages hard-coded in chainx-cli against the runtime metadata.
+    CheckCompat(compat::CheckCompat),
+
     #[cfg(feature = "sc-cli")]
     InspectKey,
 }
@@ -75,9 +107,30 @@ pub struct App {
     #[structopt(long)]
     pub uri: Option<String>,
 
+    /// Name of the account in the local keystore used as a signer.
+    ///
+    /// The password will be read from the prompt.
+    #[structopt(long, conflicts_with_all = &["signer", "uri"])]
+    pub signer_name: Option<String>,
+
+    /// Signature scheme of the signer.
+    #[structopt(
+        long,
+        default_value = "sr25519",
+        possible_values = &CryptoScheme::variants(),
+        case_insensitive = true
+    )]
+    pub scheme: CryptoScheme,
+
+    /// The local keystore directory, defaults to ~/.chainx-cli/keystore.
+    #[structopt(long, parse(from_os_str))]
+    pub keystore_path: Option<PathBuf>,
+
     /// The websocket url of ChainX node.
-    #[structopt(long, default_value = "ws://127.0.0.1:8087")]
-    pub url: String,
+    ///
+    /// Can be given multiple times, the next url is tried if the connection fails.
+    #[structopt(long, default_value = "ws://127.0.0.1:8087", number_of_values = 1)]
+    pub url: Vec<String>,
 
     /// Ss58 Address version of the network.
     ///
@@ -85,16 +138,45 @@ pub struct App {
     #[structopt(long, default_value = "44")]
     pub ss58_prefix: sp_core::crypto::Ss58AddressFormat,
 
+    /// Output format of the command result.
+    #[structopt(
+        long,
+        default_value = "text",
+        possible_values = &OutputFormat::variants(),
+        case_insensitive = true
+    )]
+    pub output: OutputFormat,
+
+    /// The block the queries default to and the extrinsics are waited for.
+    ///
+    /// With `finalized`, the queries without `--at` read the finalized head, and the
+    /// submission returns after the block including the extrinsic is finalized.
+    #[structopt(
+        long,
+        default_value = "best",
+        possible_values = &Finality::variants(),
+        case_insensitive = true
+    )]
+    pub finality: Finality,
+
+    /// What to do if the runtime metadata is incompatible with chainx-cli at startup.
+    ///
+    /// See `check-compat` for the full report.
+    #[structopt(
+        long,
+        default_value = "warn",
+        possible_values = &CompatCheck::variants(),
+        case_insensitive = true
+    )]
+    pub compat_check: CompatCheck,
+
+    #[structopt(flatten)]
+    pub tx: TxParams,
+
     #[structopt(subcommand)]
     pub command: Cmd,
 }
 
-fn as_sr25519_signer(uri: &str) -> Result<ChainXSigner> {
-    sp_core::sr25519::Pair::from_phrase(&uri, None)
-        .map(|(pair, _seed)| PairSigner::new(pair))
-        .map_err(|err| anyhow!("Failed to generate sr25519 Pair from uri: {:?}", err))
-}
-
 impl App {
     pub fn init() -> Self {
         App::from_args()
@@ -103,29 +185,68 @@ impl App {
     pub async fn run(self) -> Result<()> {
         sp_core::crypto::set_default_ss58_version(self.ss58_prefix);
 
-        let signer = if let Some(ref uri) = self.get_uri() {
-            as_sr25519_signer(uri)?
+        let keystore_path = self
+            .keystore_path
+            .clone()
+            .unwrap_or_else(Keystore::default_path);
+        let signer = if let Some(ref name) = self.signer_name {
+            keystore::load_signer(&keystore_path, name)?
+        } else if let Some(ref uri) = self.get_uri() {
+            ChainXSigner::from_uri(self.scheme, uri)?
         } else {
-            self.builtin_signer()
+            self.builtin_signer()?
         };
 
+        let output = self.output;
+        let submitter = Submitter::new(
+            self.url.clone(),
+            signer,
+            self.tx.clone(),
+            output,
+            self.finality,
+            self.compat_check,
+        );
         match self.command {
-            Cmd::Balances(balances) => balances.run(self.url, signer).await?,
-            Cmd::Session(session) => session.run(self.url, signer).await?,
-            Cmd::Sudo(sudo) => sudo.run(self.url, signer).await?,
-            Cmd::System(system) => system.run(self.url, signer).await?,
-            Cmd::XAssets(xassets) => xassets.run(self.url, signer).await?,
-            Cmd::XMiningAsset(xmining_asset) => xmining_asset.run(self.url, signer).await?,
-            Cmd::XStaking(xstaking) => xstaking.run(self.url, signer).await?,
+            Cmd::Account(account) => account.run(submitter, output).await?,
+            Cmd::Balances(balances) => balances.run(submitter, output).await?,
+            Cmd::Keystore(keystore) => keystore.run(&keystore_path, self.scheme, output)?,
+            Cmd::Runtime(runtime) => runtime.run(submitter, output).await?,
+            Cmd::Session(session) => session.run(submitter, output).await?,
+            Cmd::Sudo(sudo) => sudo.run(submitter, output).await?,
+            Cmd::System(system) => system.run(submitter, output).await?,
+            Cmd::Storage(storage) => storage.run(self.url, self.finality, output).await?,
+            Cmd::XAssets(xassets) => xassets.run(submitter, output).await?,
+            Cmd::XMiningAsset(xmining_asset) => xmining_asset.run(submitter, output).await?,
+            Cmd::XStaking(xstaking) => xstaking.run(submitter, output).await?,
+            Cmd::Sign(sign) => sign.run(submitter.signer(), output).await?,
+            Cmd::Submit(submit) => submit.run(&submitter, output).await?,
+            Cmd::CheckCompat(check_compat) => check_compat.run(self.url, output).await?,
             #[cfg(feature = "sc-cli")]
             Cmd::InspectKey => {
                 if let Some(ref uri) = self.get_uri() {
-                    sc_cli::utils::print_from_uri::<sp_core::sr25519::Pair>(
-                        uri,
-                        None,
-                        Some(self.ss58_prefix),
-                        sc_cli::OutputType::Text,
-                    );
+                    let output = match output {
+                        OutputFormat::Text => sc_cli::OutputType::Text,
+                        OutputFormat::Json => sc_cli::OutputType::Json,
+                    };
+                    let network = Some(self.ss58_prefix);
+                    // The account id is derived from the public key of the chosen scheme.
+                    match self.scheme {
+                        CryptoScheme::Sr25519 => {
+                            sc_cli::utils::print_from_uri::<sp_core::sr25519::Pair>(
+                                uri, None, network, output,
+                            )
+                        }
+                        CryptoScheme::Ed25519 => {
+                            sc_cli::utils::print_from_uri::<sp_core::ed25519::Pair>(
+                                uri, None, network, output,
+                            )
+                        }
+                        CryptoScheme::Ecdsa => {
+                            sc_cli::utils::print_from_uri::<sp_core::ecdsa::Pair>(
+                                uri, None, network, output,
+                            )
+                        }
+                    }
                 }
             }
         }
@@ -143,9 +264,15 @@ impl App {
         }
     }
 
-    fn builtin_signer(&self) -> ChainXSigner {
+    fn builtin_signer(&self) -> Result<ChainXSigner> {
         let signer = self.signer.clone().unwrap_or(BuiltinAccounts::Alice);
-        let signer: AccountKeyring = signer.into();
-        PairSigner::new(signer.pair())
+        match self.scheme {
+            CryptoScheme::Sr25519 => {
+                let signer: AccountKeyring = signer.into();
+                Ok(signer.pair().into())
+            }
+            // The builtin accounts of other schemes are derived in the same way, e.g., `//Alice`.
+            scheme => ChainXSigner::from_uri(scheme, &format!("//{}", signer)),
+        }
     }
 }
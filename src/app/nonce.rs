@@ -0,0 +1,105 @@
+//! Local nonce allocation of the sender.
+//!
+//! The next nonce is read from the chain only once, the following extrinsics get
+//! the consecutive nonces without waiting for the previous ones to be included.
+
+use std::future::Future;
+
+use anyhow::Result;
+use async_std::sync::Mutex;
+
+use crate::runtime::primitives::Index;
+
+/// Hands out the consecutive nonces of a single sender.
+#[derive(Debug, Default)]
+pub struct NonceManager {
+    next: Mutex<Option<Index>>,
+}
+
+impl NonceManager {
+    /// Creates a manager starting from `start`, or from the nonce fetched at the first use.
+    pub fn new(start: Option<Index>) -> Self {
+        Self {
+            next: Mutex::new(start),
+        }
+    }
+
+    /// Returns the next nonce without allocating it.
+    pub async fn peek<F, Fut>(&self, fetch: F) -> Result<Index>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Index>>,
+    {
+        let mut next = self.next.lock().await;
+        let nonce = match *next {
+            Some(nonce) => nonce,
+            None => fetch().await?,
+        };
+        *next = Some(nonce);
+        Ok(nonce)
+    }
+
+    /// Allocates the next nonce, `fetch` is only called if no nonce has been allocated yet.
+    pub async fn next<F, Fut>(&self, fetch: F) -> Result<Index>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Index>>,
+    {
+        let mut next = self.next.lock().await;
+        let nonce = match *next {
+            Some(nonce) => nonce,
+            None => fetch().await?,
+        };
+        *next = Some(nonce + 1);
+        Ok(nonce)
+    }
+
+    /// Skips the nonces before `min`, nothing is done if they have been allocated.
+    pub async fn skip_to<F, Fut>(&self, min: Index, fetch: F) -> Result<()>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Index>>,
+    {
+        let mut next = self.next.lock().await;
+        let nonce = match *next {
+            Some(nonce) => nonce,
+            None => fetch().await?,
+        };
+        *next = Some(nonce.max(min));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[async_std::test]
+    async fn test_nonce_manager() {
+        let fetched = &AtomicUsize::new(0);
+        let fetch = move || async move {
+            fetched.fetch_add(1, Ordering::SeqCst);
+            Ok::<_, anyhow::Error>(7)
+        };
+
+        let manager = NonceManager::new(None);
+        assert_eq!(manager.peek(fetch).await.unwrap(), 7);
+        assert_eq!(manager.next(fetch).await.unwrap(), 7);
+        assert_eq!(manager.next(fetch).await.unwrap(), 8);
+        assert_eq!(manager.peek(fetch).await.unwrap(), 9);
+        assert_eq!(fetched.load(Ordering::SeqCst), 1);
+
+        let manager = NonceManager::new(Some(3));
+        assert_eq!(manager.next(fetch).await.unwrap(), 3);
+        assert_eq!(manager.next(fetch).await.unwrap(), 4);
+        assert_eq!(fetched.load(Ordering::SeqCst), 1);
+
+        manager.skip_to(10, fetch).await.unwrap();
+        assert_eq!(manager.next(fetch).await.unwrap(), 10);
+        manager.skip_to(5, fetch).await.unwrap();
+        assert_eq!(manager.next(fetch).await.unwrap(), 11);
+        assert_eq!(fetched.load(Ordering::SeqCst), 1);
+    }
+}
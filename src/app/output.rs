@@ -0,0 +1,393 @@
+//! Serializable output of the `chainx-cli` subcommands.
+
+use std::{collections::BTreeMap, time::Duration};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sp_runtime::ApplyExtrinsicResult;
+use structopt::clap::arg_enum;
+use subxt::{balances::BalanceLock, system::AccountInfo, ExtrinsicSuccess, RawEvent};
+
+use crate::{
+    app::{errors::describe_dispatch_error, events::decode_event},
+    metadata::Metadata,
+    rpc::payment::RuntimeDispatchInfo,
+    runtime::{
+        primitives::{AccountId, Balance, BlockNumber, Hash, Index},
+        xpallets::{
+            xmining_asset::{AssetLedger, MinerLedger, MiningWeight},
+            xstaking::{NominatorLedger, Unbonded, ValidatorLedger, ValidatorProfile, VoteWeight},
+        },
+        ChainXRuntime,
+    },
+    serde::{serde_hex, serde_num_str},
+};
+
+arg_enum! {
+  #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+  pub enum OutputFormat {
+      Text,
+      Json,
+  }
+}
+
+impl Default for OutputFormat {
+    fn default() -> Self {
+        Self::Text
+    }
+}
+
+impl OutputFormat {
+    /// Prints `value` as pretty JSON in json mode, otherwise calls `text` to print it.
+    pub fn print<T, F>(self, value: &T, text: F) -> Result<()>
+    where
+        T: Serialize,
+        F: FnOnce(&T) -> Result<()>,
+    {
+        match self {
+            Self::Text => text(value),
+            Self::Json => {
+                println!("{}", serde_json::to_string_pretty(value)?);
+                Ok(())
+            }
+        }
+    }
+
+    /// Prints the debug format of `value` prefixed with `title` in text mode.
+    pub fn print_debug<T: Serialize + std::fmt::Debug>(self, title: &str, value: &T) -> Result<()> {
+        self.print(value, |value| {
+            println!("{}: {:#?}", title, value);
+            Ok(())
+        })
+    }
+}
+
+/// A balance like number that is serialized as a string.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(transparent)]
+pub struct Amount(#[serde(with = "serde_num_str")] pub u128);
+
+impl From<u128> for Amount {
+    fn from(value: u128) -> Self {
+        Self(value)
+    }
+}
+
+/// Converts a map of balances into a map of [`Amount`].
+pub fn amounts<K: Ord>(map: BTreeMap<K, Balance>) -> BTreeMap<K, Amount> {
+    map.into_iter().map(|(k, v)| (k, v.into())).collect()
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventOutput {
+    pub module: String,
+    pub variant: String,
+    #[serde(with = "serde_hex")]
+    pub data: Vec<u8>,
+    /// Decoded arguments of the known events.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub args: Option<Map<String, Value>>,
+    #[serde(skip)]
+    text: Option<String>,
+}
+
+impl EventOutput {
+    /// Decodes the known events, the undecodable ones are kept as the raw bytes.
+    fn new(metadata: &Metadata, event: &RawEvent) -> (Self, Option<String>) {
+        let (args, text, error) = match decode_event(metadata, event) {
+            Ok(Some(decoded)) => (
+                Some(decoded.args),
+                Some(decoded.text.join(", ")),
+                decoded.error,
+            ),
+            Ok(None) | Err(_) => (None, None, None),
+        };
+        let output = Self {
+            module: event.module.clone(),
+            variant: event.variant.clone(),
+            data: event.data.clone(),
+            args,
+            text,
+        };
+        (output, error)
+    }
+
+    fn print_text(&self) {
+        match self.text {
+            Some(ref text) => println!("  {}::{} {{ {} }}", self.module, self.variant, text),
+            None => println!(
+                "  {}::{} 0x{}",
+                self.module,
+                self.variant,
+                hex::encode(&self.data)
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtrinsicOutput {
+    pub block: Hash,
+    pub extrinsic: Hash,
+    pub events: Vec<EventOutput>,
+    /// The error of the failed dispatch, e.g., the call of `sudo` fails.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Milliseconds from the inclusion to the finalization, with `--finality finalized`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finalized_after_ms: Option<u64>,
+}
+
+impl ExtrinsicOutput {
+    pub fn new(metadata: &Metadata, result: &ExtrinsicSuccess<ChainXRuntime>) -> Self {
+        let mut events = Vec::with_capacity(result.events.len());
+        let mut error = None;
+        for event in &result.events {
+            let (event, event_error) = EventOutput::new(metadata, event);
+            events.push(event);
+            error = error.or(event_error);
+        }
+        Self {
+            block: result.block,
+            extrinsic: result.extrinsic,
+            events,
+            error,
+            finalized_after_ms: None,
+        }
+    }
+
+    /// Sets the time it took to finalize the block since the inclusion.
+    pub fn finalized_after(mut self, elapsed: Option<Duration>) -> Self {
+        self.finalized_after_ms = elapsed.map(|elapsed| elapsed.as_millis() as u64);
+        self
+    }
+
+    /// Prints the finalization of the block if it has been waited for.
+    pub fn print_finality(&self) {
+        if let Some(ms) = self.finalized_after_ms {
+            println!(
+                "Finalized: block {:?} is finalized {:.1}s after the inclusion",
+                self.block,
+                ms as f64 / 1000.0
+            );
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// Prints the decoded events and whether the extrinsic succeeded.
+    pub fn print_text(&self) -> Result<()> {
+        println!("Events:");
+        for event in &self.events {
+            event.print_text();
+        }
+        match self.error {
+            None => println!(
+                "Success: extrinsic {:?} is included in block {:?}",
+                self.extrinsic, self.block
+            ),
+            Some(ref error) => println!(
+                "Failure: {} (extrinsic {:?} is included in block {:?})",
+                error, self.extrinsic, self.block
+            ),
+        }
+        self.print_finality();
+        Ok(())
+    }
+}
+
+/// The estimated fee and the dispatch outcome of an extrinsic which is not submitted.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DryRunOutput {
+    pub sender: AccountId,
+    pub nonce: Index,
+    #[serde(flatten)]
+    pub info: RuntimeDispatchInfo,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+impl DryRunOutput {
+    /// The module errors are decoded with `metadata`.
+    pub fn new(
+        metadata: &Metadata,
+        sender: AccountId,
+        nonce: Index,
+        info: RuntimeDispatchInfo,
+        result: ApplyExtrinsicResult,
+    ) -> Self {
+        let error = match result {
+            Ok(Ok(())) => None,
+            Ok(Err(err)) => Some(format!(
+                "Dispatch error: {}",
+                describe_dispatch_error(metadata, err)
+            )),
+            Err(err) => Some(format!("Invalid transaction: {:?}", err)),
+        };
+        Self {
+            sender,
+            nonce,
+            info,
+            success: error.is_none(),
+            error,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountInfoOutput {
+    pub who: AccountId,
+    pub nonce: Index,
+    pub refcount: u32,
+    pub free: Amount,
+    pub reserved: Amount,
+    pub misc_frozen: Amount,
+    pub fee_frozen: Amount,
+}
+
+impl AccountInfoOutput {
+    pub fn new(who: AccountId, info: AccountInfo<ChainXRuntime>) -> Self {
+        Self {
+            who,
+            nonce: info.nonce,
+            refcount: info.refcount.into(),
+            free: info.data.free.into(),
+            reserved: info.data.reserved.into(),
+            misc_frozen: info.data.misc_frozen.into(),
+            fee_frozen: info.data.fee_frozen.into(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BalanceLockOutput {
+    pub id: String,
+    pub amount: Amount,
+    pub reasons: String,
+}
+
+impl From<BalanceLock<Balance>> for BalanceLockOutput {
+    fn from(lock: BalanceLock<Balance>) -> Self {
+        Self {
+            id: String::from_utf8_lossy(&lock.id).to_string(),
+            amount: lock.amount.into(),
+            reasons: format!("{:?}", lock.reasons),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorProfileOutput {
+    pub registered_at: BlockNumber,
+    pub is_chilled: bool,
+    pub last_chilled: Option<BlockNumber>,
+    pub referral_id: String,
+}
+
+impl From<ValidatorProfile<BlockNumber>> for ValidatorProfileOutput {
+    fn from(profile: ValidatorProfile<BlockNumber>) -> Self {
+        Self {
+            registered_at: profile.registered_at,
+            is_chilled: profile.is_chilled,
+            last_chilled: profile.last_chilled,
+            referral_id: String::from_utf8_lossy(&profile.referral_id).to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorLedgerOutput {
+    pub total_nomination: Amount,
+    pub last_total_vote_weight: Amount,
+    pub last_total_vote_weight_update: BlockNumber,
+}
+
+impl From<ValidatorLedger<Balance, VoteWeight, BlockNumber>> for ValidatorLedgerOutput {
+    fn from(ledger: ValidatorLedger<Balance, VoteWeight, BlockNumber>) -> Self {
+        Self {
+            total_nomination: ledger.total_nomination.into(),
+            last_total_vote_weight: ledger.last_total_vote_weight.into(),
+            last_total_vote_weight_update: ledger.last_total_vote_weight_update,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnbondedOutput {
+    pub value: Amount,
+    pub locked_until: BlockNumber,
+}
+
+impl From<Unbonded<Balance, BlockNumber>> for UnbondedOutput {
+    fn from(unbonded: Unbonded<Balance, BlockNumber>) -> Self {
+        Self {
+            value: unbonded.value.into(),
+            locked_until: unbonded.locked_until,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NominatorLedgerOutput {
+    pub nomination: Amount,
+    pub last_vote_weight: Amount,
+    pub last_vote_weight_update: BlockNumber,
+    pub unbonded_chunks: Vec<UnbondedOutput>,
+}
+
+impl From<NominatorLedger<Balance, VoteWeight, BlockNumber>> for NominatorLedgerOutput {
+    fn from(ledger: NominatorLedger<Balance, VoteWeight, BlockNumber>) -> Self {
+        Self {
+            nomination: ledger.nomination.into(),
+            last_vote_weight: ledger.last_vote_weight.into(),
+            last_vote_weight_update: ledger.last_vote_weight_update,
+            unbonded_chunks: ledger.unbonded_chunks.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetLedgerOutput {
+    pub last_total_mining_weight: Amount,
+    pub last_total_mining_weight_update: BlockNumber,
+}
+
+impl From<AssetLedger<MiningWeight, BlockNumber>> for AssetLedgerOutput {
+    fn from(ledger: AssetLedger<MiningWeight, BlockNumber>) -> Self {
+        Self {
+            last_total_mining_weight: ledger.last_total_mining_weight.into(),
+            last_total_mining_weight_update: ledger.last_total_mining_weight_update,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MinerLedgerOutput {
+    pub last_mining_weight: Amount,
+    pub last_mining_weight_update: BlockNumber,
+    pub last_claim: Option<BlockNumber>,
+}
+
+impl From<MinerLedger<MiningWeight, BlockNumber>> for MinerLedgerOutput {
+    fn from(ledger: MinerLedger<MiningWeight, BlockNumber>) -> Self {
+        Self {
+            last_mining_weight: ledger.last_mining_weight.into(),
+            last_mining_weight_update: ledger.last_mining_weight_update,
+            last_claim: ledger.last_claim,
+        }
+    }
+}
@@ -0,0 +1,298 @@
+//! Runtime upgrade with the version of the new code verified before submitting.
+
+use std::{marker::PhantomData, path::PathBuf, time::Duration};
+
+use anyhow::{anyhow, Result};
+use codec::Decode;
+use serde::Serialize;
+use sp_core::blake2_256;
+use sp_version::RuntimeVersion;
+use structopt::StructOpt;
+use subxt::{
+    sudo::SudoUncheckedWeightCall,
+    system::{CodeUpdatedEventExt, SetCodeCall, SetCodeWithoutChecksCall},
+};
+
+use crate::{
+    app::{
+        output::{ExtrinsicOutput, OutputFormat},
+        sudo::CallWeight,
+        tx::Submitter,
+    },
+    runtime::{primitives::Hash, ChainXRuntime},
+    utils::read_code,
+};
+
+/// Name of the wasm custom section `sp_version::runtime_version` embeds the version in.
+const RUNTIME_VERSION_SECTION: &[u8] = b"runtime_version";
+
+/// Interval of polling the runtime version after the upgrade.
+const VERSION_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Max times of polling the runtime version after the upgrade.
+const VERSION_POLL_ATTEMPTS: usize = 30;
+
+/// Runtime
+#[derive(Debug, StructOpt)]
+pub enum Runtime {
+    /// Upgrade the runtime by `sudo_unchecked_weight(set_code)`.
+    ///
+    /// The spec name and version embedded in the code are compared with the live chain
+    /// before submitting, then the new version is confirmed on chain.
+    Upgrade {
+        /// Code path
+        #[structopt(index = 1, long, parse(from_os_str))]
+        code: PathBuf,
+        /// Submit even if the spec name mismatches or the spec version is not greater.
+        #[structopt(long)]
+        force: bool,
+        /// Use `set_code_without_checks`, which skips the same checks on chain.
+        #[structopt(long)]
+        without_checks: bool,
+        /// Weight of `set_code`, a number or `auto`.
+        ///
+        /// Defaults to 0 so that the code taking the whole block is not rejected for
+        /// exceeding the block weight. `auto` queries the weight by `payment_queryInfo`.
+        #[structopt(long, default_value = "0")]
+        weight: CallWeight,
+    },
+}
+
+fn take<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+    if input.len() < len {
+        return Err(anyhow!("Unexpected end of the wasm code"));
+    }
+    let (taken, rest) = input.split_at(len);
+    *input = rest;
+    Ok(taken)
+}
+
+fn read_leb128(input: &mut &[u8]) -> Result<usize> {
+    let mut value = 0usize;
+    for shift in (0..35).step_by(7) {
+        let byte = take(input, 1)?[0];
+        value |= ((byte & 0x7f) as usize) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(anyhow!("Invalid LEB128 integer in the wasm code"))
+}
+
+/// Reads the `RuntimeVersion` embedded in the `runtime_version` custom section of the wasm code.
+pub fn read_runtime_version(code: &[u8]) -> Result<RuntimeVersion> {
+    let mut input = code;
+    if take(&mut input, 8).ok() != Some(&b"\0asm\x01\0\0\0"[..]) {
+        return Err(anyhow!("Not a wasm binary of version 1"));
+    }
+    while !input.is_empty() {
+        let id = take(&mut input, 1)?[0];
+        let size = read_leb128(&mut input)?;
+        let mut section = take(&mut input, size)?;
+        // Custom sections have the id 0 and start with the name.
+        if id == 0 {
+            let name_len = read_leb128(&mut section)?;
+            if take(&mut section, name_len)? == RUNTIME_VERSION_SECTION {
+                return Ok(RuntimeVersion::decode(&mut section)?);
+            }
+        }
+    }
+    Err(anyhow!("No runtime_version section found in the wasm code"))
+}
+
+/// Checks that `new` is an upgrade of the same runtime as `current`.
+fn check_upgrade(current: &RuntimeVersion, new: &RuntimeVersion) -> Result<()> {
+    if new.spec_name != current.spec_name {
+        return Err(anyhow!(
+            "Spec name `{}` of the code does not match `{}` on chain",
+            new.spec_name,
+            current.spec_name
+        ));
+    }
+    if new.spec_version <= current.spec_version {
+        return Err(anyhow!(
+            "Spec version {} of the code is not greater than {} on chain",
+            new.spec_version,
+            current.spec_version
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UpgradeOutput {
+    code_hash: Hash,
+    code_len: usize,
+    current_version: RuntimeVersion,
+    new_version: RuntimeVersion,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    extrinsic: Option<ExtrinsicOutput>,
+    /// Whether the new spec version is confirmed on chain.
+    upgraded: bool,
+}
+
+impl UpgradeOutput {
+    fn print_summary(&self) {
+        println!("Code hash: {:?} ({} bytes)", self.code_hash, self.code_len);
+        println!(
+            "Spec: {} {} => {} {}",
+            self.current_version.spec_name,
+            self.current_version.spec_version,
+            self.new_version.spec_name,
+            self.new_version.spec_version
+        );
+    }
+}
+
+impl Runtime {
+    pub async fn run(self, submitter: Submitter, output: OutputFormat) -> Result<()> {
+        let client = submitter.client().await?;
+        let rpc = submitter.rpc().await?;
+
+        match self {
+            Self::Upgrade {
+                code,
+                force,
+                without_checks,
+                weight,
+            } => {
+                let code = read_code(code)?;
+                let new_version = read_runtime_version(&code)?;
+                let current_version = rpc.runtime_version(None).await?;
+                let mut upgrade = UpgradeOutput {
+                    code_hash: Hash::from(blake2_256(&code)),
+                    code_len: code.len(),
+                    current_version,
+                    new_version,
+                    extrinsic: None,
+                    upgraded: false,
+                };
+                if output == OutputFormat::Text {
+                    upgrade.print_summary();
+                }
+                if let Err(err) = check_upgrade(&upgrade.current_version, &upgrade.new_version) {
+                    if !force {
+                        return Err(anyhow!("{}, use --force to upgrade anyway", err));
+                    }
+                    eprintln!("Warning: {}, forced to upgrade", err);
+                }
+
+                let set_code = if without_checks {
+                    client.encode(SetCodeWithoutChecksCall::<ChainXRuntime> {
+                        _runtime: PhantomData,
+                        code: &code,
+                    })?
+                } else {
+                    client.encode(SetCodeCall::<ChainXRuntime> {
+                        _runtime: PhantomData,
+                        code: &code,
+                    })?
+                };
+                let weight = weight.resolve(&submitter, &set_code, output).await?;
+                let call = SudoUncheckedWeightCall::<ChainXRuntime> {
+                    _runtime: PhantomData,
+                    call: &set_code,
+                    weight,
+                };
+                let result = match submitter.submit(&client, call).await? {
+                    Some(result) => result,
+                    None => return Ok(()),
+                };
+                let extrinsic = submitter.extrinsic_output(&result).await?;
+                if let Some(ref err) = extrinsic.error {
+                    return Err(anyhow!("Dispatch failed: {}", err));
+                }
+                if result.code_updated()?.is_none() {
+                    return Err(anyhow!(
+                        "No System::CodeUpdated event in block {:?}",
+                        result.block
+                    ));
+                }
+                if output == OutputFormat::Text {
+                    println!("Code updated in block {:?}", result.block);
+                }
+                upgrade.extrinsic = Some(extrinsic);
+
+                for _ in 0..VERSION_POLL_ATTEMPTS {
+                    let version = rpc.runtime_version(None).await?;
+                    if version.spec_version == upgrade.new_version.spec_version {
+                        upgrade.upgraded = true;
+                        break;
+                    }
+                    async_std::task::sleep(VERSION_POLL_INTERVAL).await;
+                }
+                output.print(&upgrade, |upgrade| {
+                    if upgrade.upgraded {
+                        println!(
+                            "Runtime upgraded to {} {}",
+                            upgrade.new_version.spec_name, upgrade.new_version.spec_version
+                        );
+                    }
+                    Ok(())
+                })?;
+                if !upgrade.upgraded {
+                    return Err(anyhow!(
+                        "Spec version {} is not confirmed on chain after the code is updated",
+                        upgrade.new_version.spec_version
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use codec::Encode;
+    use std::borrow::Cow;
+
+    fn version(spec_name: &'static str, spec_version: u32) -> RuntimeVersion {
+        RuntimeVersion {
+            spec_name: spec_name.into(),
+            impl_name: spec_name.into(),
+            authoring_version: 1,
+            spec_version,
+            impl_version: 1,
+            apis: Cow::Owned(vec![([1; 8], 1)]),
+            transaction_version: 1,
+        }
+    }
+
+    fn section(id: u8, content: &[u8]) -> Vec<u8> {
+        assert!(content.len() < 0x80 * 0x80);
+        let mut section = vec![id, content.len() as u8 | 0x80, (content.len() >> 7) as u8];
+        section.extend_from_slice(content);
+        section
+    }
+
+    #[test]
+    fn test_read_runtime_version() {
+        let expected = version("chainx", 2);
+        let mut custom = vec![RUNTIME_VERSION_SECTION.len() as u8];
+        custom.extend_from_slice(RUNTIME_VERSION_SECTION);
+        custom.extend(expected.encode());
+
+        let mut code = b"\0asm\x01\0\0\0".to_vec();
+        code.extend(section(1, &[0; 200]));
+        code.extend(section(0, b"\x04name"));
+        code.extend(section(0, &custom));
+        assert_eq!(read_runtime_version(&code).unwrap(), expected);
+
+        assert!(read_runtime_version(&code[..code.len() - 1]).is_err());
+        assert!(read_runtime_version(b"\0asm\x01\0\0\0").is_err());
+        assert!(read_runtime_version(b"not wasm").is_err());
+    }
+
+    #[test]
+    fn test_check_upgrade() {
+        assert!(check_upgrade(&version("chainx", 1), &version("chainx", 2)).is_ok());
+        assert!(check_upgrade(&version("chainx", 2), &version("chainx", 2)).is_err());
+        assert!(check_upgrade(&version("chainx", 2), &version("chainx", 1)).is_err());
+        assert!(check_upgrade(&version("chainx", 1), &version("node", 2)).is_err());
+    }
+}
@@ -1,59 +1,123 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use codec::{Decode, Encode};
+use serde::Serialize;
+use sp_runtime::traits::OpaqueKeys;
 
 use crate::{
-    frame::session::{NextKeysStoreExt, ValidatorsStoreExt},
-    runtime::{primitives::BlockNumber, ChainXSigner},
-    utils::{block_hash, build_client},
+    app::{output::OutputFormat, tx::Submitter},
+    frame::session::{SetKeysCall, ValidatorsStoreExt},
+    runtime::{primitives::AccountId, BasicSessionKeys, ChainXRuntime, SESSION_KEY_LEN},
+    serde::serde_hex,
+    utils::AtBlock,
 };
 
 /// Session
 #[derive(structopt::StructOpt, Debug)]
 pub enum Session {
+    /// Set the session keys of signer.
     SetKeys {
+        /// Hex encoded session keys, i.e., the result of RPC `author_rotateKeys`.
         #[structopt(index = 1, long)]
         keys: String,
     },
     Validators {
-        #[structopt(long)]
-        block_number: Option<BlockNumber>,
+        #[structopt(flatten)]
+        at: AtBlock,
     },
     NextKeys {
-        #[structopt(long)]
-        block_number: Option<BlockNumber>,
+        #[structopt(flatten)]
+        at: AtBlock,
     },
 }
 
+/// Decodes the hex blob returned by `author_rotateKeys` into `BasicSessionKeys`.
+fn parse_session_keys(keys: &str) -> Result<BasicSessionKeys> {
+    let keys = keys.strip_prefix("0x").unwrap_or(keys);
+    let encoded = hex::decode(keys)?;
+
+    let expected_len = BasicSessionKeys::key_ids().len() * SESSION_KEY_LEN;
+    if encoded.len() != expected_len {
+        return Err(anyhow!(
+            "Invalid session keys length, expected {} bytes ({} keys), got {} bytes",
+            expected_len,
+            BasicSessionKeys::key_ids().len(),
+            encoded.len()
+        ));
+    }
+
+    BasicSessionKeys::decode(&mut encoded.as_slice())
+        .map_err(|err| anyhow!("Failed to decode session keys: {:?}", err))
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NextKeysOutput {
+    validator: AccountId,
+    referral_id: String,
+    #[serde(with = "serde_hex")]
+    keys: Vec<u8>,
+}
+
 impl Session {
-    pub async fn run(self, url: String, _signer: ChainXSigner) -> Result<()> {
-        let client = build_client(url).await?;
+    pub async fn run(self, submitter: Submitter, output: OutputFormat) -> Result<()> {
+        let client = submitter.client().await?;
 
         match self {
-            Self::Validators { block_number } => {
-                let at = block_hash(&client, block_number).await?;
-                println!("{:#?}", client.validators(at).await?);
+            Self::Validators { at } => {
+                let rpc = submitter.rpc().await?;
+                let at = at.hash(&rpc, submitter.finality()).await?;
+                let validators = client.validators(at).await?;
+                output.print(&validators, |validators| {
+                    println!("{:#?}", validators);
+                    Ok(())
+                })?;
             }
             Self::SetKeys { keys } => {
-                let _ = keys;
-                todo!()
-                // let result = client.set_keys_and_watch(&signer, &call).await?;
-                // println!("{:#?}", result);
+                let keys = parse_session_keys(&keys)?;
+                if output == OutputFormat::Text {
+                    println!("Session keys: {:#?}", keys);
+                }
+
+                let call = SetKeysCall::<ChainXRuntime> {
+                    keys,
+                    proof: Vec::new(),
+                };
+                submitter.submit_and_print(&client, call).await?;
             }
 
-            Self::NextKeys { block_number } => {
-                let at = block_hash(&client, block_number).await?;
+            Self::NextKeys { at } => {
+                let rpc = submitter.rpc().await?;
+                let at = at.hash(&rpc, submitter.finality()).await?;
                 let validators = client.validators(at).await?;
+                let metadata = rpc.metadata(at).await?;
+                let profiles = rpc.get_validators(at).await?;
 
+                let mut next_keys = Vec::with_capacity(validators.len());
                 for validator in validators {
-                    let keys = client.next_keys(&validator, at).await?;
+                    let keys: Option<BasicSessionKeys> = rpc
+                        .get_map_value(&metadata, "Session", "NextKeys", &validator, at)
+                        .await?;
                     if let Some(keys) = keys {
-                        let referral_id =
-                            crate::utils::get_referral_id(&client, &validator, at).await?;
-                        println!(
-                            "{:?}\n{}({}): {:#?}",
-                            validator, validator, referral_id, keys
-                        );
+                        let referral_id = profiles
+                            .get(&validator)
+                            .map(|profile| {
+                                String::from_utf8_lossy(&profile.referral_id).to_string()
+                            })
+                            .unwrap_or_default();
+                        if output == OutputFormat::Text {
+                            println!(
+                                "{:?}\n{}({}): {:#?}",
+                                validator, validator, referral_id, keys
+                            );
+                        }
+                        next_keys.push(NextKeysOutput {
+                            validator,
+                            referral_id,
+                            keys: keys.encode(),
+                        });
                     }
                 }
+                output.print(&next_keys, |_| Ok(()))?;
             }
         }
 
@@ -0,0 +1,159 @@
+//! Generic storage queries driven by the runtime metadata.
+
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value;
+use sp_core::storage::StorageKey;
+use structopt::StructOpt;
+
+use crate::{
+    app::output::OutputFormat,
+    rpc::Rpc,
+    utils::{AtBlock, Finality},
+};
+
+/// Query any storage item using the runtime metadata, e.g., `storage XStaking Validators <ACCOUNT>`.
+///
+/// All the entries are listed if the keys of a map are omitted, the first key of a double map
+/// can be given alone to list the entries under it.
+#[derive(Debug, StructOpt)]
+pub struct Storage {
+    /// module name, e.g., XStaking
+    #[structopt(index = 1)]
+    module: String,
+    /// storage item name, e.g., Validators
+    #[structopt(index = 2)]
+    item: String,
+    /// map keys, e.g., an address, a number or a JSON value
+    #[structopt(index = 3)]
+    keys: Vec<String>,
+    #[structopt(flatten)]
+    at: AtBlock,
+    /// Number of the entries fetched in one page when listing a map.
+    #[structopt(long, default_value = "1000")]
+    page_size: u32,
+}
+
+#[derive(Debug, Serialize)]
+struct StorageEntryOutput {
+    keys: Vec<Value>,
+    value: Value,
+}
+
+impl StorageEntryOutput {
+    fn print_text(&self) {
+        let value = serde_json::to_string_pretty(&self.value).unwrap_or_default();
+        if self.keys.is_empty() {
+            println!("{}", value);
+        } else {
+            let keys = self
+                .keys
+                .iter()
+                .map(|key| match key {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .collect::<Vec<_>>();
+            println!("{} => {}", keys.join(", "), value);
+        }
+    }
+}
+
+/// Prints the entries as they are fetched, so that a large map is never held in memory.
+///
+/// The json output is the same object of `module`, `item` and `entries` as a whole.
+struct EntryPrinter {
+    output: OutputFormat,
+    module: String,
+    item: String,
+    count: usize,
+}
+
+impl EntryPrinter {
+    fn begin(output: OutputFormat, module: &str, item: &str) -> Result<Self> {
+        if output == OutputFormat::Json {
+            print!(
+                "{{\n  \"module\": {},\n  \"item\": {},\n  \"entries\": [",
+                serde_json::to_string(module)?,
+                serde_json::to_string(item)?
+            );
+        }
+        Ok(Self {
+            output,
+            module: module.into(),
+            item: item.into(),
+            count: 0,
+        })
+    }
+
+    fn print(&mut self, entry: &StorageEntryOutput) -> Result<()> {
+        match self.output {
+            OutputFormat::Text => entry.print_text(),
+            OutputFormat::Json => {
+                let separator = if self.count == 0 { "" } else { "," };
+                let entry = serde_json::to_string_pretty(entry)?.replace('\n', "\n    ");
+                print!("{}\n    {}", separator, entry);
+            }
+        }
+        self.count += 1;
+        Ok(())
+    }
+
+    fn finish(self) {
+        match self.output {
+            OutputFormat::Text if self.count == 0 => {
+                println!("No entries of {}::{}", self.module, self.item)
+            }
+            OutputFormat::Text => {}
+            OutputFormat::Json if self.count == 0 => println!("]\n}}"),
+            OutputFormat::Json => println!("\n  ]\n}}"),
+        }
+    }
+}
+
+impl Storage {
+    pub async fn run(
+        self,
+        urls: Vec<String>,
+        finality: Finality,
+        output: OutputFormat,
+    ) -> Result<()> {
+        let rpc = Rpc::new(&urls).await?.with_page_size(self.page_size);
+        let at = self.at.hash(&rpc, finality).await?;
+        let metadata = rpc.metadata(at).await?;
+
+        let module = metadata.module(&self.module)?;
+        let storage = module.storage(&self.item)?;
+        let key = metadata.encode_storage_key(&module.name, &storage.name, &self.keys)?;
+
+        let mut printer = EntryPrinter::begin(output, &module.name, &storage.name)?;
+        if self.keys.len() == storage.keys().len() {
+            let value = rpc.get_storage(&StorageKey(key.clone()), at).await?;
+            printer.print(&StorageEntryOutput {
+                keys: metadata.decode_storage_key(&module.name, &storage.name, &key)?,
+                value: metadata.decode_storage_value(
+                    &module.name,
+                    &storage.name,
+                    value.as_ref().map(|value| value.0.as_slice()),
+                )?,
+            })?;
+        } else {
+            let mut pairs = rpc.iter_pairs(StorageKey(key), at);
+            while let Some(page) = pairs.next_page().await? {
+                for (key, value) in page {
+                    printer.print(&StorageEntryOutput {
+                        keys: metadata.decode_storage_key(&module.name, &storage.name, &key.0)?,
+                        value: metadata.decode_storage_value(
+                            &module.name,
+                            &storage.name,
+                            Some(&value.0),
+                        )?,
+                    })?;
+                }
+            }
+        }
+        printer.finish();
+
+        Ok(())
+    }
+}
@@ -1,27 +1,83 @@
-use std::{marker::PhantomData, path::PathBuf};
+use std::{
+    marker::PhantomData,
+    path::{Path, PathBuf},
+};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use frame_support::weights::Weight;
 use structopt::StructOpt;
 use subxt::{
-    sudo::{SudoCallExt, SudoUncheckedWeightCallExt},
+    sudo::{SudoCall, SudoUncheckedWeightCall},
     system::{SetCodeCall, SetCodeWithoutChecksCall},
     Encoded,
 };
 
 use crate::{
+    app::{output::OutputFormat, tx::Submitter},
+    metadata::Metadata,
     runtime::{
         primitives::*,
         xpallets::xstaking::{SetSessionsPerEraCall, SetValidatorCountCall},
-        ChainXClient, ChainXRuntime, ChainXSigner,
+        ChainXClient, ChainXRuntime,
     },
-    utils::{build_client, read_code},
+    utils::read_code,
 };
 
 /// Sudo
 #[derive(Debug, StructOpt)]
 pub enum Sudo {
     Sudo(Calls),
-    SudoUncheckedWeight(Calls),
+    SudoUncheckedWeight {
+        /// Weight of the inner call, a number or `auto`.
+        ///
+        /// Defaults to 0 so that an oversized call like `set_code` is not rejected.
+        /// `auto` queries the weight of the inner call by `payment_queryInfo`.
+        #[structopt(long, default_value = "0")]
+        weight: CallWeight,
+        #[structopt(subcommand)]
+        calls: Calls,
+    },
+}
+
+/// Weight declared for the call dispatched by `sudo_unchecked_weight`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CallWeight {
+    Auto,
+    Fixed(Weight),
+}
+
+impl CallWeight {
+    /// Returns the weight to declare for the SCALE encoded `call`, the weight of `auto` is
+    /// queried by `payment_queryInfo`.
+    pub async fn resolve(
+        self,
+        submitter: &Submitter,
+        call: &Encoded,
+        output: OutputFormat,
+    ) -> Result<Weight> {
+        match self {
+            Self::Fixed(weight) => Ok(weight),
+            Self::Auto => {
+                let info = submitter.query_info(call.0.clone()).await?;
+                if output == OutputFormat::Text {
+                    println!("Weight: {} (queried by payment_queryInfo)", info.weight);
+                }
+                Ok(info.weight)
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for CallWeight {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("auto") {
+            Ok(Self::Auto)
+        } else {
+            Ok(Self::Fixed(s.parse()?))
+        }
+    }
 }
 
 #[derive(Debug, StructOpt)]
@@ -30,6 +86,46 @@ pub enum Calls {
     System(System),
     #[structopt(name = "xstaking")]
     XStaking(XStaking),
+    /// A SCALE encoded call in hex.
+    Raw {
+        #[structopt(index = 1, long)]
+        call: String,
+    },
+    /// Read the SCALE encoded call from a file, either in hex or in raw bytes.
+    File {
+        #[structopt(index = 1, long, parse(from_os_str))]
+        path: PathBuf,
+    },
+    /// Any call of the runtime, the arguments are encoded according to the metadata.
+    ///
+    /// The arguments are parsed by their types, the strings like a nickname are taken as
+    /// is and the others as JSON if possible, e.g.,
+    /// `sudo call XStaking set_validator_count 30`.
+    Call {
+        /// Pallet name, e.g., XStaking.
+        #[structopt(index = 1)]
+        pallet: String,
+        /// Call name, e.g., set_validator_count.
+        #[structopt(index = 2)]
+        call: String,
+        /// Arguments of the call.
+        #[structopt(index = 3)]
+        args: Vec<String>,
+    },
+}
+
+fn decode_hex(hex: &str) -> Result<Vec<u8>> {
+    let hex = hex.trim();
+    Ok(hex::decode(hex.strip_prefix("0x").unwrap_or(hex))?)
+}
+
+/// Reads the call from `path`, the content is treated as hex if possible.
+fn read_call(path: &Path) -> Result<Vec<u8>> {
+    let data = read_code(path)?;
+    match std::str::from_utf8(&data).map(decode_hex) {
+        Ok(Ok(call)) => Ok(call),
+        _ => Ok(data),
+    }
 }
 
 #[derive(Debug, StructOpt)]
@@ -58,11 +154,10 @@ pub enum XStaking {
 }
 
 impl Calls {
-    pub fn as_encoded(&self, client: &ChainXClient) -> Result<Encoded> {
+    pub fn as_encoded(&self, client: &ChainXClient, metadata: &Metadata) -> Result<Encoded> {
         match self {
             Self::System(system) => match system {
                 System::SetCode { code } => {
-                    println!("System::SetCode:");
                     let code = read_code(code)?;
                     Ok(client.encode(SetCodeCall::<ChainXRuntime> {
                         _runtime: PhantomData,
@@ -70,7 +165,6 @@ impl Calls {
                     })?)
                 }
                 System::SetCodeWithoutChecks { code } => {
-                    println!("System::SetCodeWithoutChecks:");
                     let code = read_code(code)?;
                     Ok(client.encode(SetCodeWithoutChecksCall::<ChainXRuntime> {
                         _runtime: PhantomData,
@@ -80,41 +174,78 @@ impl Calls {
             },
             Self::XStaking(xstaking) => match xstaking {
                 XStaking::SetValidatorCount { new } => {
-                    println!("sudo XStaking::SetValidatorCount:");
                     Ok(client.encode(SetValidatorCountCall::<ChainXRuntime> {
                         _runtime: PhantomData,
                         new: *new,
                     })?)
                 }
                 XStaking::SetSessionsPerEra { new } => {
-                    println!("sudo XStaking::SetSessionsPerEra:");
                     Ok(client.encode(SetSessionsPerEraCall::<ChainXRuntime> {
                         _runtime: PhantomData,
                         new: *new,
                     })?)
                 }
             },
+            Self::Raw { call } => Ok(Encoded(decode_hex(call)?)),
+            Self::File { path } => Ok(Encoded(read_call(path)?)),
+            Self::Call { pallet, call, args } => {
+                Ok(Encoded(metadata.encode_call(pallet, call, args)?))
+            }
+        }
+    }
+}
+
+/// Prints the decoded call so that it can be confirmed before submitting.
+fn print_call(metadata: &Metadata, call: &Encoded, output: OutputFormat) -> Result<()> {
+    if output != OutputFormat::Text {
+        return Ok(());
+    }
+    let mut input = call.0.as_slice();
+    match metadata.decode_call(&mut input) {
+        Ok(_) if !input.is_empty() => Err(anyhow!(
+            "Invalid call: {} trailing bytes after decoding",
+            input.len()
+        )),
+        Ok(decoded) => {
+            println!("Call: {:#?}", decoded.abbreviated());
+            Ok(())
+        }
+        Err(err) => {
+            println!(
+                "Call: 0x{} (failed to decode: {})",
+                hex::encode(&call.0),
+                err
+            );
+            Ok(())
         }
     }
 }
 
 impl Sudo {
-    pub async fn run(self, url: String, signer: ChainXSigner) -> Result<()> {
-        let client = build_client(url).await?;
+    pub async fn run(self, submitter: Submitter, output: OutputFormat) -> Result<()> {
+        let client = submitter.client().await?;
+        let metadata = submitter.metadata().await?;
 
-        println!("Sudo");
         match self {
             Self::Sudo(calls) => {
-                let call = calls.as_encoded(&client)?;
-                let result = client.sudo_and_watch(&signer, &call).await?;
-                println!("{:#?}", result);
+                let call = calls.as_encoded(&client, &metadata)?;
+                print_call(&metadata, &call, output)?;
+                let sudo_call = SudoCall::<ChainXRuntime> {
+                    _runtime: PhantomData,
+                    call: &call,
+                };
+                submitter.submit_and_print(&client, sudo_call).await?;
             }
-            Self::SudoUncheckedWeight(calls) => {
-                let call = calls.as_encoded(&client)?;
-                let result = client
-                    .sudo_unchecked_weight_and_watch(&signer, &call, 0u64)
-                    .await?;
-                println!("{:#?}", result);
+            Self::SudoUncheckedWeight { weight, calls } => {
+                let call = calls.as_encoded(&client, &metadata)?;
+                print_call(&metadata, &call, output)?;
+                let weight = weight.resolve(&submitter, &call, output).await?;
+                let sudo_call = SudoUncheckedWeightCall::<ChainXRuntime> {
+                    _runtime: PhantomData,
+                    call: &call,
+                    weight,
+                };
+                submitter.submit_and_print(&client, sudo_call).await?;
             }
         }
 
@@ -1,15 +1,16 @@
-use std::path::PathBuf;
+use std::{marker::PhantomData, path::PathBuf};
 
 use anyhow::Result;
 use structopt::StructOpt;
-use subxt::system::{AccountStoreExt, SetCodeWithoutChecksCallExt};
+use subxt::system::{AccountStoreExt, SetCodeWithoutChecksCall};
 
 use crate::{
-    runtime::{
-        primitives::{AccountId, BlockNumber},
-        ChainXSigner,
+    app::{
+        output::{AccountInfoOutput, OutputFormat},
+        tx::Submitter,
     },
-    utils::{block_hash, build_client, parse_account, read_code},
+    runtime::{primitives::AccountId, ChainXRuntime},
+    utils::{parse_account, read_code, AtBlock},
 };
 
 /// System
@@ -19,8 +20,8 @@ pub enum System {
     AccountInfo {
         #[structopt(index = 1, long, parse(try_from_str = parse_account))]
         who: AccountId,
-        #[structopt(long)]
-        block_number: Option<BlockNumber>,
+        #[structopt(flatten)]
+        at: AtBlock,
     },
     /// Set code without checking.
     SetCodeWithoutChecks {
@@ -31,20 +32,29 @@ pub enum System {
 }
 
 impl System {
-    pub async fn run(self, url: String, signer: ChainXSigner) -> Result<()> {
-        let client = build_client(url).await?;
+    pub async fn run(self, submitter: Submitter, output: OutputFormat) -> Result<()> {
+        let client = submitter.client().await?;
 
         match self {
-            Self::AccountInfo { who, block_number } => {
-                let at = block_hash(&client, block_number).await?;
+            Self::AccountInfo { who, at } => {
+                let rpc = submitter.rpc().await?;
+                let at = at.hash(&rpc, submitter.finality()).await?;
                 let account_info = client.account(&who, at).await?;
-                println!("AccountInfo of {:?}: {:#?}", who, account_info);
+                output.print(
+                    &AccountInfoOutput::new(who.clone(), account_info.clone()),
+                    |_| {
+                        println!("AccountInfo of {:?}: {:#?}", who, account_info);
+                        Ok(())
+                    },
+                )?;
             }
             Self::SetCodeWithoutChecks { code } => {
-                let result = client
-                    .set_code_without_checks_and_watch(&signer, &read_code(code)?)
-                    .await?;
-                println!("set_code_without_checks result:{:#?}", result);
+                let code = read_code(code)?;
+                let call = SetCodeWithoutChecksCall::<ChainXRuntime> {
+                    _runtime: PhantomData,
+                    code: &code,
+                };
+                submitter.submit_and_print(&client, call).await?;
             }
         }
 
@@ -0,0 +1,823 @@
+//! Transaction workflow of `chainx-cli`.
+//!
+//! An extrinsic can be submitted directly, or be built, signed and broadcast in three
+//! separate steps so that the key never leaves the offline machine:
+//!
+//! 1. `chainx-cli --unsigned-only unsigned.json <subcommand>` writes the unsigned payload.
+//! 2. `chainx-cli sign unsigned.json signed.json` signs it offline.
+//! 3. `chainx-cli submit signed.json` broadcasts the signed extrinsic.
+//!
+//! With `--dry-run`, the extrinsic is signed but only checked against the node by
+//! `payment_queryInfo` and `system_dryRun`, it's never submitted.
+//!
+//! With `--mortality <blocks>`, the transaction is only valid for about that many blocks
+//! after it's built, so that a leaked signed transaction can't be replayed later. `--tip`
+//! is paid to the block author on top of the fee to prioritise the transaction.
+//!
+//! With `--finality finalized`, the submission returns after the block including the
+//! extrinsic is finalized by GRANDPA, not just included.
+//!
+//! The nonce of the sender is read from the chain once and then allocated locally, so that
+//! the extrinsics can be submitted concurrently by `Submitter::submit_all`. `--nonce`
+//! overrides the first nonce, e.g., to fill a gap left by a dropped extrinsic.
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use codec::{Compact, Decode, Encode};
+use futures::{future::LocalBoxFuture, stream::FuturesUnordered, FutureExt};
+use jsonrpsee::client::RequestError;
+use serde::{Deserialize, Serialize};
+use sp_runtime::generic::Era;
+use structopt::StructOpt;
+use subxt::{
+    extrinsic::{SignedExtra, SignedPayload, UncheckedExtrinsic},
+    Call, Encoded, Error as SubxtError, ExtrinsicSuccess, Runtime, Signer,
+};
+
+use crate::{
+    app::{
+        errors::describe_runtime_error,
+        nonce::NonceManager,
+        output::{DryRunOutput, ExtrinsicOutput, OutputFormat},
+    },
+    metadata::{compat::CompatCheck, Metadata},
+    rpc::{payment::RuntimeDispatchInfo, Rpc},
+    runtime::{
+        events_decoder,
+        primitives::{AccountId, Address, Balance, BlockNumber, Hash, Index, Signature},
+        ChainXClient, ChainXRuntime, ChainXSigner,
+    },
+    serde::{serde_hex, serde_num_str},
+    units::{parse_pcx, PCX},
+    utils::{build_client_on, parse_account, Finality},
+};
+
+/// Transaction options shared by all the subcommands submitting an extrinsic.
+#[derive(Debug, Clone, StructOpt)]
+pub struct TxParams {
+    /// Write the unsigned transaction to the given file instead of submitting it.
+    ///
+    /// The file can be signed offline by the `sign` subcommand later.
+    #[structopt(long, parse(from_os_str))]
+    pub unsigned_only: Option<PathBuf>,
+
+    /// Sign the transaction and print the estimated fee and the dispatch result, without submitting it.
+    ///
+    /// The fee and weight are queried by `payment_queryInfo`, the dispatch result by `system_dryRun`.
+    #[structopt(long, conflicts_with = "unsigned-only")]
+    pub dry_run: bool,
+
+    /// The account which will sign the unsigned transaction, defaults to the signer.
+    #[structopt(long, parse(try_from_str = parse_account))]
+    pub sender: Option<AccountId>,
+
+    /// The nonce of the first extrinsic, defaults to `system_accountNextIndex` of the sender.
+    ///
+    /// The following extrinsics of the same run take the consecutive nonces.
+    #[structopt(long)]
+    pub nonce: Option<Index>,
+
+    /// Make the transaction mortal, only valid for the given number of blocks.
+    ///
+    /// The period is rounded to a power of two between 4 and 65536, starting from the
+    /// finalized head when the transaction is built. The transaction is immortal by default.
+    #[structopt(long)]
+    pub mortality: Option<u64>,
+
+    /// The tip paid to the block author to prioritise the transaction, e.g., `0.1PCX`.
+    #[structopt(long, default_value = "0", parse(try_from_str = parse_pcx))]
+    pub tip: Balance,
+}
+
+/// The unsigned transaction with everything needed for signing it offline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsignedTransaction {
+    /// The account expected to sign this transaction.
+    pub sender: AccountId,
+    /// SCALE encoded call.
+    #[serde(with = "serde_hex")]
+    pub call: Vec<u8>,
+    pub nonce: Index,
+    /// SCALE encoded transaction era.
+    #[serde(with = "serde_hex")]
+    pub era: Vec<u8>,
+    /// Hash of the block the mortal era starts at.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub era_hash: Option<Hash>,
+    #[serde(default, with = "serde_num_str")]
+    pub tip: Balance,
+    pub genesis_hash: Hash,
+    pub spec_version: u32,
+    pub transaction_version: u32,
+}
+
+/// The signed transaction ready for broadcasting.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedTransaction {
+    pub sender: AccountId,
+    pub nonce: Index,
+    /// SCALE encoded extrinsic.
+    #[serde(with = "serde_hex")]
+    pub extrinsic: Vec<u8>,
+}
+
+pub fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T> {
+    let file = File::open(path)?;
+    Ok(serde_json::from_reader(file)?)
+}
+
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    let file = File::create(path)?;
+    Ok(serde_json::to_writer_pretty(file, value)?)
+}
+
+impl UnsignedTransaction {
+    /// Signs the transaction, no network access is required.
+    pub async fn sign(self, signer: &ChainXSigner) -> Result<SignedTransaction> {
+        if signer.account_id() != &self.sender {
+            return Err(anyhow!(
+                "Signer {} does not match the sender {} of the unsigned transaction",
+                signer.account_id(),
+                self.sender
+            ));
+        }
+
+        let era = Era::decode(&mut self.era.as_slice())?;
+        let era_hash = match (era, self.era_hash) {
+            (Era::Immortal, _) => self.genesis_hash,
+            (Era::Mortal(..), Some(era_hash)) => era_hash,
+            (Era::Mortal(..), None) => {
+                return Err(anyhow!("Block hash of the mortal era {:?} is missing", era))
+            }
+        };
+
+        let extra = <ChainXRuntime as Runtime>::Extra::new(
+            self.spec_version,
+            self.transaction_version,
+            self.nonce,
+            self.genesis_hash,
+        )
+        .era(era, era_hash)
+        .tip(self.tip);
+        let payload = SignedPayload::<ChainXRuntime>::new(Encoded(self.call), extra.extra())
+            .map_err(|err| anyhow!("Failed to build the signed payload: {:?}", err))?;
+        let extrinsic = signer.sign(payload).await.map_err(|err| anyhow!(err))?;
+
+        Ok(SignedTransaction {
+            sender: self.sender,
+            nonce: self.nonce,
+            extrinsic: extrinsic.encode(),
+        })
+    }
+}
+
+impl SignedTransaction {
+    /// Decodes the SCALE encoded extrinsic.
+    ///
+    /// The call is kept as the opaque `Encoded` bytes as it's not known statically.
+    pub fn decode_extrinsic(&self) -> Result<UncheckedExtrinsic<ChainXRuntime>> {
+        let mut input = self.extrinsic.as_slice();
+        let _len = <Compact<u32>>::decode(&mut input)?;
+        let version = u8::decode(&mut input)?;
+        if version & 0b1000_0000 == 0 {
+            return Err(anyhow!("Extrinsic is not signed"));
+        }
+        let address = Address::decode(&mut input)?;
+        let signature = Signature::decode(&mut input)?;
+        let extra =
+            <<ChainXRuntime as Runtime>::Extra as SignedExtra<ChainXRuntime>>::Extra::decode(
+                &mut input,
+            )?;
+        Ok(UncheckedExtrinsic::<ChainXRuntime>::new_signed(
+            Encoded(input.to_vec()),
+            address,
+            signature,
+            extra,
+        ))
+    }
+}
+
+/// Converts the failed dispatch reported by subxt into an error with the name and
+/// documentation of the module error.
+fn check_dispatch(
+    metadata: &Metadata,
+    result: std::result::Result<ExtrinsicSuccess<ChainXRuntime>, SubxtError>,
+) -> Result<ExtrinsicSuccess<ChainXRuntime>> {
+    match result {
+        Ok(result) => Ok(result),
+        Err(SubxtError::Runtime(err)) => {
+            Err(DispatchFailed(describe_runtime_error(metadata, &err)).into())
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// The extrinsic is included, but the dispatch failed.
+#[derive(Debug)]
+pub struct DispatchFailed(pub String);
+
+impl std::fmt::Display for DispatchFailed {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Dispatch failed: {}", self.0)
+    }
+}
+
+impl std::error::Error for DispatchFailed {}
+
+/// Max times an extrinsic dropped by the pool or lost with the connection is resubmitted with
+/// the same nonce.
+const MAX_RESUBMISSIONS: usize = 3;
+
+/// Returns true if the extrinsic may be included by resubmitting it, i.e., it's dropped by the
+/// pool or the connection is broken.
+///
+/// The extrinsic rejected by the node, e.g., `1010: Invalid Transaction` for the fee which
+/// can't be paid or the bad signature, will never be included, nor the usurped one whose nonce
+/// is taken by another extrinsic.
+fn is_resubmittable(err: &SubxtError) -> bool {
+    match err {
+        SubxtError::Rpc(RequestError::TransportError(_)) | SubxtError::Io(_) => true,
+        // "Extrinsic Dropped" by the pool, or the subscription dropped with the connection.
+        SubxtError::Other(err) => err.to_lowercase().contains("dropped"),
+        _ => false,
+    }
+}
+
+/// Interval of polling the finalized head while waiting for the finalization.
+const FINALITY_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Waits until `block` is finalized, returns the time it took.
+///
+/// Returns an error if another block of the same number is finalized, i.e., `block` is retracted.
+async fn wait_finalized(rpc: &Rpc, block: Hash) -> Result<Duration> {
+    let start = Instant::now();
+    let number = rpc
+        .header(Some(block))
+        .await?
+        .ok_or_else(|| anyhow!("Header of block {:?} not found", block))?
+        .number;
+    loop {
+        let finalized_hash = rpc.finalized_head().await?;
+        let finalized = rpc
+            .header(Some(finalized_hash))
+            .await?
+            .ok_or_else(|| {
+                anyhow!(
+                    "Header of the finalized block {:?} not found",
+                    finalized_hash
+                )
+            })?
+            .number;
+        if finalized >= number {
+            let canonical = rpc.block_hash(number).await?;
+            if canonical != Some(block) {
+                return Err(anyhow!(
+                    "Block {:?} is retracted, block #{} is finalized as {:?}",
+                    block,
+                    number,
+                    canonical
+                ));
+            }
+            return Ok(start.elapsed());
+        }
+        async_std::task::sleep(FINALITY_POLL_INTERVAL).await;
+    }
+}
+
+/// Returns the mortal era of `period` blocks starting from the finalized head, and the hash of
+/// the block it starts at.
+///
+/// The finalized head is used so that the block the era starts at is never retracted.
+async fn mortal_era(rpc: &Rpc, period: u64) -> Result<(Era, Hash)> {
+    let finalized_hash = rpc.finalized_head().await?;
+    let current = rpc
+        .header(Some(finalized_hash))
+        .await?
+        .ok_or_else(|| {
+            anyhow!(
+                "Header of the finalized block {:?} not found",
+                finalized_hash
+            )
+        })?
+        .number;
+    let era = Era::mortal(period, current.into());
+    let birth = era.birth(current.into()) as BlockNumber;
+    let era_hash = rpc
+        .block_hash(birth)
+        .await?
+        .ok_or_else(|| anyhow!("Hash of block #{} not found", birth))?;
+    Ok((era, era_hash))
+}
+
+/// The extrinsic with `nonce` is not included even after the resubmissions.
+#[derive(Debug)]
+pub struct NonceGap {
+    pub nonce: Index,
+    pub error: String,
+}
+
+impl std::fmt::Display for NonceGap {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Extrinsic with nonce {} is not included: {}, the following extrinsics are blocked \
+             until the nonce is used, run again with `--nonce {}` to fill the gap",
+            self.nonce, self.error, self.nonce
+        )
+    }
+}
+
+impl std::error::Error for NonceGap {}
+
+/// The nonce of the extrinsic has been used on chain, but the extrinsic was not watched
+/// until its inclusion, e.g., the subscription is dropped.
+#[derive(Debug)]
+pub struct NonceUsed {
+    pub nonce: Index,
+    pub error: String,
+}
+
+impl std::fmt::Display for NonceUsed {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Extrinsic with nonce {} is included but not watched: {}, check its result on chain",
+            self.nonce, self.error
+        )
+    }
+}
+
+impl std::error::Error for NonceUsed {}
+
+/// Item of `Submitter::submit_all`, `(index of the call, dispatch result)`.
+pub type WatchResult = (usize, Result<ExtrinsicSuccess<ChainXRuntime>>);
+
+/// Prints the decoded events of the included extrinsic.
+///
+/// Returns an error if the dispatch failed, e.g., the call of `sudo` fails.
+fn print_extrinsic(output: OutputFormat, extrinsic: ExtrinsicOutput) -> Result<()> {
+    output.print(&extrinsic, |extrinsic| extrinsic.print_text())?;
+    match extrinsic.error {
+        Some(err) => Err(anyhow!("Dispatch failed: {}", err)),
+        None => Ok(()),
+    }
+}
+
+/// The chain parameters every transaction is signed with.
+#[derive(Clone, Copy, Debug)]
+struct ChainParams {
+    genesis_hash: Hash,
+    spec_version: u32,
+    transaction_version: u32,
+}
+
+/// Submits the extrinsics of subcommands according to the transaction options.
+pub struct Submitter {
+    urls: Vec<String>,
+    signer: ChainXSigner,
+    params: TxParams,
+    output: OutputFormat,
+    finality: Finality,
+    compat_check: CompatCheck,
+    /// The connection shared by all the RPCs, opened at the first call.
+    rpc: async_std::sync::Mutex<Option<Rpc>>,
+    /// The client built on the connection of `rpc` after the compatibility check, with the
+    /// generation of the connection.
+    client: async_std::sync::Mutex<Option<(ChainXClient, u64)>>,
+    metadata: Mutex<Option<Arc<Metadata>>>,
+    chain_params: Mutex<Option<ChainParams>>,
+    nonce: NonceManager,
+    /// Time it took to finalize the submitted extrinsics, by the extrinsic hash.
+    finalized: Mutex<HashMap<Hash, Duration>>,
+}
+
+impl Submitter {
+    pub fn new(
+        urls: Vec<String>,
+        signer: ChainXSigner,
+        params: TxParams,
+        output: OutputFormat,
+        finality: Finality,
+        compat_check: CompatCheck,
+    ) -> Self {
+        Self {
+            nonce: NonceManager::new(params.nonce),
+            urls,
+            signer,
+            params,
+            output,
+            finality,
+            compat_check,
+            rpc: async_std::sync::Mutex::new(None),
+            client: async_std::sync::Mutex::new(None),
+            metadata: Mutex::new(None),
+            chain_params: Mutex::new(None),
+            finalized: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn signer(&self) -> &ChainXSigner {
+        &self.signer
+    }
+
+    /// Returns the account sending the extrinsics, i.e., `--sender` or the signer.
+    pub fn sender(&self) -> AccountId {
+        self.params
+            .sender
+            .clone()
+            .unwrap_or_else(|| self.signer.account_id().clone())
+    }
+
+    /// Returns the finality the queries default to and the extrinsics are waited for.
+    pub fn finality(&self) -> Finality {
+        self.finality
+    }
+
+    /// Returns true if the unsigned transaction is written instead of submitting it.
+    pub fn is_unsigned_only(&self) -> bool {
+        self.params.unsigned_only.is_some()
+    }
+
+    /// Returns true if the extrinsics are only dry run.
+    pub fn is_dry_run(&self) -> bool {
+        self.params.dry_run
+    }
+
+    /// Returns the connection for the RPCs not provided by the client, which is opened at
+    /// the first call and shared by the following ones.
+    pub async fn rpc(&self) -> Result<Rpc> {
+        let mut rpc = self.rpc.lock().await;
+        if let Some(ref rpc) = *rpc {
+            return Ok(rpc.clone());
+        }
+        let connected = Rpc::new(&self.urls).await?;
+        *rpc = Some(connected.clone());
+        Ok(connected)
+    }
+
+    /// Returns the client sharing the connection of [`Submitter::rpc`], which is built at the
+    /// first call after the compatibility check passes.
+    ///
+    /// The client is rebuilt on the new connection once `rpc` fails over, so that it's never
+    /// left on the failed one.
+    pub async fn client(&self) -> Result<ChainXClient> {
+        let mut client = self.client.lock().await;
+        let rpc = self.rpc().await?;
+        let generation = rpc.generation().await;
+        if let Some((ref client, built_on)) = *client {
+            if built_on == generation {
+                return Ok(client.clone());
+            }
+        }
+        let built = build_client_on(&rpc, self.compat_check).await?;
+        *client = Some((built.clone(), generation));
+        Ok(built)
+    }
+
+    /// Returns the runtime metadata, which is fetched at the first call.
+    pub async fn metadata(&self) -> Result<Arc<Metadata>> {
+        if let Some(ref metadata) = *self.metadata.lock().expect("Metadata lock poisoned") {
+            return Ok(metadata.clone());
+        }
+        let metadata = Arc::new(self.rpc().await?.metadata(None).await?);
+        *self.metadata.lock().expect("Metadata lock poisoned") = Some(metadata.clone());
+        Ok(metadata)
+    }
+
+    /// Returns the genesis hash and the runtime version, which are fetched at the first call.
+    async fn chain_params(&self) -> Result<ChainParams> {
+        if let Some(params) = *self
+            .chain_params
+            .lock()
+            .expect("Chain params lock poisoned")
+        {
+            return Ok(params);
+        }
+        let rpc = self.rpc().await?;
+        let runtime_version = rpc.runtime_version(None).await?;
+        let params = ChainParams {
+            genesis_hash: rpc.genesis_hash().await?,
+            spec_version: runtime_version.spec_version,
+            transaction_version: runtime_version.transaction_version,
+        };
+        *self
+            .chain_params
+            .lock()
+            .expect("Chain params lock poisoned") = Some(params);
+        Ok(params)
+    }
+
+    /// Builds the output of the included extrinsic with the events decoded.
+    pub async fn extrinsic_output(
+        &self,
+        result: &ExtrinsicSuccess<ChainXRuntime>,
+    ) -> Result<ExtrinsicOutput> {
+        let finalized = self
+            .finalized
+            .lock()
+            .expect("Finalized lock poisoned")
+            .get(&result.extrinsic)
+            .copied();
+        Ok(ExtrinsicOutput::new(&*self.metadata().await?, result).finalized_after(finalized))
+    }
+
+    /// Submits the call and prints the decoded events once it's included in a block.
+    ///
+    /// Returns an error if the dispatch failed.
+    pub async fn submit_and_print<C>(&self, client: &ChainXClient, call: C) -> Result<()>
+    where
+        C: Call<ChainXRuntime> + Send + Sync,
+    {
+        if let Some(result) = self.submit(client, call).await? {
+            print_extrinsic(self.output, self.extrinsic_output(&result).await?)?;
+        }
+        Ok(())
+    }
+
+    /// Submits the call and watches it until it's included in a block, or finalized with
+    /// `--finality finalized`.
+    ///
+    /// Returns an error if the dispatch failed, i.e., `System::ExtrinsicFailed`.
+    /// Returns `None` if the call is not submitted, e.g., `--unsigned-only` or `--dry-run`
+    /// is specified.
+    pub async fn submit<C>(
+        &self,
+        client: &ChainXClient,
+        call: C,
+    ) -> Result<Option<ExtrinsicSuccess<ChainXRuntime>>>
+    where
+        C: Call<ChainXRuntime> + Send + Sync,
+    {
+        if let Some(ref path) = self.params.unsigned_only {
+            let sender = self.sender();
+            let call = client.encode(call)?.0;
+            let nonce = self.nonce.next(|| self.fetch_nonce(&sender)).await?;
+            let unsigned = self.build_unsigned(sender, call, nonce).await?;
+            write_json(path, &unsigned)?;
+            self.output.print(&unsigned, |_| {
+                println!(
+                    "Unsigned transaction has been written to {}",
+                    path.display()
+                );
+                Ok(())
+            })?;
+            return Ok(None);
+        }
+
+        if self.params.dry_run {
+            self.dry_run(client.encode(call)?.0).await?;
+            return Ok(None);
+        }
+
+        let call = client.encode(call)?.0;
+        let nonce = self.next_nonce().await?;
+        self.watch(&call, nonce).await.map(Some)
+    }
+
+    /// Submits the encoded calls signed with the given nonces at once, and tracks all of them
+    /// until they're included, or finalized with `--finality finalized`.
+    ///
+    /// The nonces are allocated by [`Submitter::next_nonce`] beforehand, so that the caller
+    /// can record them before the submission.
+    /// The stream yields `(index in calls, dispatch result)` in the order of inclusion.
+    /// An extrinsic which can't be included even after the resubmissions is yielded with
+    /// [`NonceGap`], the extrinsics with the greater nonces are blocked by the gap and will
+    /// not be yielded until the nonce is used.
+    pub fn submit_all(
+        &self,
+        calls: Vec<(Index, Encoded)>,
+    ) -> FuturesUnordered<LocalBoxFuture<'_, WatchResult>> {
+        calls
+            .into_iter()
+            .enumerate()
+            .map(|(index, (nonce, call))| {
+                async move { (index, self.watch(&call.0, nonce).await) }.boxed_local()
+            })
+            .collect()
+    }
+
+    /// Submits the SCALE encoded call signed with `nonce`, and watches it until it's included.
+    ///
+    /// The extrinsic dropped by the pool, or lost with the connection, leaves a gap at `nonce`,
+    /// which blocks the following extrinsics of the signer, so it's signed again and
+    /// resubmitted with the same nonce a block later, [`NonceGap`] is returned if it still
+    /// fails. The extrinsic rejected by the pool, e.g., the fee can't be paid, is never
+    /// resubmitted.
+    /// It's never resubmitted once the nonce is used on chain, [`NonceUsed`] is returned then,
+    /// as the extrinsic may have been included while the subscription failed.
+    async fn watch(&self, call: &[u8], nonce: Index) -> Result<ExtrinsicSuccess<ChainXRuntime>> {
+        let metadata = self.metadata().await?;
+        // A block is expected every two minimum periods of the timestamp.
+        let block_time =
+            Duration::from_millis(2 * metadata.constant::<u64>("Timestamp", "MinimumPeriod")?);
+        let mut resubmissions = 0;
+        let result = loop {
+            // Taken for every submission, as it's rebuilt after a failover.
+            let client = self.client().await?;
+            let signed = self.sign_call(call.to_vec(), nonce).await?;
+            let decoder = events_decoder(&client);
+            match client
+                .submit_and_watch_extrinsic(signed.decode_extrinsic()?, decoder)
+                .await
+            {
+                Err(err) if is_resubmittable(&err) => {
+                    let error = err.to_string();
+                    // The extrinsic may still be included if only the subscription failed.
+                    async_std::task::sleep(block_time).await;
+                    if self.is_nonce_used(nonce).await? {
+                        return Err(NonceUsed { nonce, error }.into());
+                    }
+                    if resubmissions == MAX_RESUBMISSIONS {
+                        return Err(NonceGap { nonce, error }.into());
+                    }
+                    resubmissions += 1;
+                    eprintln!(
+                        "Extrinsic with nonce {} is not included: {}, resubmitting ({}/{})",
+                        nonce, error, resubmissions, MAX_RESUBMISSIONS
+                    );
+                }
+                result => break result,
+            }
+        };
+        let result = check_dispatch(&metadata, result)?;
+        if self.finality == Finality::Finalized {
+            let elapsed = wait_finalized(&self.rpc().await?, result.block).await?;
+            self.finalized
+                .lock()
+                .expect("Finalized lock poisoned")
+                .insert(result.extrinsic, elapsed);
+        }
+        Ok(result)
+    }
+
+    /// Fetches the next nonce of `who` from the chain.
+    async fn fetch_nonce(&self, who: &AccountId) -> Result<Index> {
+        self.rpc().await?.account_next_index(who).await
+    }
+
+    /// Allocates the next nonce of the signer.
+    pub async fn next_nonce(&self) -> Result<Index> {
+        let signer = self.signer.account_id();
+        self.nonce.next(|| self.fetch_nonce(signer)).await
+    }
+
+    /// Skips the nonces of the signer before `min`, e.g., the ones allocated by a previous run.
+    pub async fn skip_nonces_to(&self, min: Index) -> Result<()> {
+        let signer = self.signer.account_id();
+        self.nonce.skip_to(min, || self.fetch_nonce(signer)).await
+    }
+
+    /// Returns true if the extrinsic of the signer with `nonce` is included in the best block,
+    /// i.e., the nonce of the signer on chain has passed it.
+    pub async fn is_nonce_used(&self, nonce: Index) -> Result<bool> {
+        let metadata = self.metadata().await?;
+        let info = self
+            .rpc()
+            .await?
+            .get_account_info(&metadata, self.signer.account_id(), None)
+            .await?;
+        Ok(info.nonce > nonce)
+    }
+
+    /// Returns the next nonce of the signer without allocating it, e.g., for the extrinsics
+    /// never submitted.
+    async fn peek_nonce(&self) -> Result<Index> {
+        let signer = self.signer.account_id();
+        self.nonce.peek(|| self.fetch_nonce(signer)).await
+    }
+
+    /// Prints the estimated fee and the dispatch result of the SCALE encoded call.
+    async fn dry_run(&self, call: Vec<u8>) -> Result<()> {
+        let signed = self.sign_call(call, self.peek_nonce().await?).await?;
+        let rpc = self.rpc().await?;
+        let info = rpc.query_info(&signed.extrinsic, None).await?;
+        let result = rpc.dry_run(&signed.extrinsic, None).await?;
+        let dry_run = DryRunOutput::new(
+            &*self.metadata().await?,
+            signed.sender,
+            signed.nonce,
+            info,
+            result,
+        );
+        self.output.print(&dry_run, |dry_run| {
+            println!("Dry run, the transaction is not submitted");
+            println!("Sender: {} (nonce {})", dry_run.sender, dry_run.nonce);
+            println!("Weight: {}", dry_run.info.weight);
+            println!("Class: {:?}", dry_run.info.class);
+            println!("Partial fee: {}", PCX.format(dry_run.info.partial_fee));
+            match dry_run.error {
+                Some(ref error) => println!("Result: {}", error),
+                None => println!("Result: Success"),
+            }
+            Ok(())
+        })
+    }
+
+    /// Queries the dispatch info of the SCALE encoded call by `payment_queryInfo`.
+    ///
+    /// The call is wrapped in an extrinsic signed by the signer, which is never submitted.
+    pub async fn query_info(&self, call: Vec<u8>) -> Result<RuntimeDispatchInfo> {
+        let signed = self.sign_call(call, self.peek_nonce().await?).await?;
+        self.rpc().await?.query_info(&signed.extrinsic, None).await
+    }
+
+    /// Signs the SCALE encoded call with `nonce`.
+    async fn sign_call(&self, call: Vec<u8>, nonce: Index) -> Result<SignedTransaction> {
+        let sender = self.signer.account_id().clone();
+        let unsigned = self.build_unsigned(sender, call, nonce).await?;
+        unsigned.sign(&self.signer).await
+    }
+
+    async fn build_unsigned(
+        &self,
+        sender: AccountId,
+        call: Vec<u8>,
+        nonce: Index,
+    ) -> Result<UnsignedTransaction> {
+        let chain_params = self.chain_params().await?;
+        let (era, era_hash) = match self.params.mortality {
+            Some(period) => {
+                let (era, era_hash) = mortal_era(&self.rpc().await?, period).await?;
+                (era, Some(era_hash))
+            }
+            None => (Era::Immortal, None),
+        };
+        Ok(UnsignedTransaction {
+            sender,
+            call,
+            nonce,
+            era: era.encode(),
+            era_hash,
+            tip: self.params.tip,
+            genesis_hash: chain_params.genesis_hash,
+            spec_version: chain_params.spec_version,
+            transaction_version: chain_params.transaction_version,
+        })
+    }
+}
+
+/// Sign an unsigned transaction offline.
+#[derive(Debug, StructOpt)]
+pub struct Sign {
+    /// The unsigned transaction file generated by `--unsigned-only`.
+    #[structopt(index = 1, long, parse(from_os_str))]
+    pub unsigned: PathBuf,
+    /// The file the signed transaction will be written to.
+    #[structopt(index = 2, long, parse(from_os_str))]
+    pub signed: PathBuf,
+}
+
+impl Sign {
+    pub async fn run(self, signer: &ChainXSigner, output: OutputFormat) -> Result<()> {
+        let unsigned: UnsignedTransaction = read_json(&self.unsigned)?;
+        let signed = unsigned.sign(signer).await?;
+        write_json(&self.signed, &signed)?;
+        output.print(&signed, |_| {
+            println!(
+                "Signed transaction has been written to {}",
+                self.signed.display()
+            );
+            Ok(())
+        })
+    }
+}
+
+/// Broadcast a signed transaction and watch it.
+#[derive(Debug, StructOpt)]
+pub struct Submit {
+    /// The signed transaction file generated by `sign`.
+    #[structopt(index = 1, long, parse(from_os_str))]
+    pub signed: PathBuf,
+}
+
+impl Submit {
+    pub async fn run(self, submitter: &Submitter, output: OutputFormat) -> Result<()> {
+        let signed: SignedTransaction = read_json(&self.signed)?;
+        let extrinsic = signed.decode_extrinsic()?;
+
+        let metadata = submitter.metadata().await?;
+        let client = submitter.client().await?;
+        let decoder = events_decoder(&client);
+        let result = client.submit_and_watch_extrinsic(extrinsic, decoder).await;
+        let result = check_dispatch(&metadata, result)?;
+        let finalized = match submitter.finality() {
+            Finality::Best => None,
+            Finality::Finalized => {
+                Some(wait_finalized(&submitter.rpc().await?, result.block).await?)
+            }
+        };
+        print_extrinsic(
+            output,
+            ExtrinsicOutput::new(&metadata, &result).finalized_after(finalized),
+        )
+    }
+}
@@ -1,15 +1,24 @@
+use std::collections::BTreeMap;
+
 use anyhow::Result;
+use serde::Serialize;
 use structopt::StructOpt;
 
 use crate::{
+    app::{
+        batch::{read_transfers, submit_batch, BatchParams},
+        output::{amounts, Amount, OutputFormat},
+        tx::Submitter,
+    },
     runtime::{
-        primitives::{AccountId, AssetId, BlockNumber},
+        primitives::{AccountId, AssetId},
         xpallets::xassets::{
-            AssetBalanceStoreExt, TotalAssetBalanceStoreExt, TransferCallExt, TransferEventExt,
+            AssetBalanceStoreExt, AssetType, TotalAssetBalanceStoreExt, TransferCall,
         },
-        ChainXSigner,
+        ChainXRuntime,
     },
-    utils::{block_hash, build_client, parse_account},
+    units::parse_asset_amount,
+    utils::{parse_account, AtBlock},
 };
 
 /// XAssets
@@ -23,9 +32,17 @@ pub enum XAssets {
         /// asset id
         #[structopt(index = 2)]
         asset_id: AssetId,
-        /// amount
+        /// amount, e.g., `1.5X-BTC`, `1.5` or `150000000` in the raw units
         #[structopt(index = 3)]
-        value: u128,
+        value: String,
+    },
+    /// Transfer some assets to the accounts in a CSV or JSON file, in batches of `utility.batch_all`.
+    BatchTransfer {
+        /// asset id
+        #[structopt(index = 1)]
+        asset_id: AssetId,
+        #[structopt(flatten)]
+        batch: BatchParams,
     },
     Storage(Storage),
 }
@@ -37,20 +54,29 @@ pub enum Storage {
         account_id: AccountId,
         #[structopt(index = 2, long)]
         asset_id: AssetId,
-        #[structopt(long)]
-        block_number: Option<BlockNumber>,
+        #[structopt(flatten)]
+        at: AtBlock,
     },
     TotalAssetBalance {
         #[structopt(index = 1, long)]
         asset_id: AssetId,
-        #[structopt(long)]
-        block_number: Option<BlockNumber>,
+        #[structopt(flatten)]
+        at: AtBlock,
     },
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AssetBalanceOutput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    who: Option<AccountId>,
+    asset_id: AssetId,
+    balance: BTreeMap<AssetType, Amount>,
+}
+
 impl XAssets {
-    pub async fn run(self, url: String, signer: ChainXSigner) -> Result<()> {
-        let client = build_client(url).await?;
+    pub async fn run(self, submitter: Submitter, output: OutputFormat) -> Result<()> {
+        let client = submitter.client().await?;
 
         match self {
             Self::Transfer {
@@ -58,35 +84,63 @@ impl XAssets {
                 asset_id,
                 value,
             } => {
-                let result = client
-                    .transfer_and_watch(&signer, &dest.into(), asset_id, value)
-                    .await?;
-                if let Some(event) = result.transfer()? {
-                    println!("XAssets transfer success: value: {:?}", event.amount);
-                } else {
-                    println!("Failed to find XAssets::Transfer Event");
+                let call = TransferCall::<ChainXRuntime> {
+                    dest: &dest.into(),
+                    asset_id,
+                    value: parse_asset_amount(asset_id, &value)?,
+                };
+                submitter.submit_and_print(&client, call).await?;
+            }
+            Self::BatchTransfer { asset_id, batch } => {
+                let transfers = read_transfers(&batch.file, asset_id)?;
+                let mut calls = Vec::with_capacity(transfers.len());
+                for transfer in &transfers {
+                    calls.push(client.encode(TransferCall::<ChainXRuntime> {
+                        dest: &transfer.dest.clone().into(),
+                        asset_id,
+                        value: transfer.value,
+                    })?);
                 }
+                submit_batch(
+                    &client, &submitter, output, &batch, asset_id, &transfers, calls,
+                )
+                .await?;
             }
             Self::Storage(storage) => match storage {
                 Storage::AssetBalance {
                     account_id,
                     asset_id,
-                    block_number,
+                    at,
                 } => {
-                    let at = block_hash(&client, block_number).await?;
+                    let rpc = submitter.rpc().await?;
+                    let at = at.hash(&rpc, submitter.finality()).await?;
                     let asset_balance = client.asset_balance(&account_id, asset_id, at).await?;
-                    println!("AssetBalance of {:?}: {:#?}", account_id, asset_balance);
+                    let asset_balance_output = AssetBalanceOutput {
+                        who: Some(account_id.clone()),
+                        asset_id,
+                        balance: amounts(asset_balance.clone()),
+                    };
+                    output.print(&asset_balance_output, |_| {
+                        println!("AssetBalance of {:?}: {:#?}", account_id, asset_balance);
+                        Ok(())
+                    })?;
                 }
-                Storage::TotalAssetBalance {
-                    asset_id,
-                    block_number,
-                } => {
-                    let at = block_hash(&client, block_number).await?;
+                Storage::TotalAssetBalance { asset_id, at } => {
+                    let rpc = submitter.rpc().await?;
+                    let at = at.hash(&rpc, submitter.finality()).await?;
                     let total_asset_balance = client.total_asset_balance(asset_id, at).await?;
-                    println!(
-                        "TotalAssetBalance of {:?}: {:#?}",
-                        asset_id, total_asset_balance
-                    );
+                    let total_asset_balance_output = AssetBalanceOutput {
+                        who: None,
+                        asset_id,
+                        balance: amounts(total_asset_balance.clone()),
+                    };
+                    output.print(&total_asset_balance_output, |_| {
+                        println!(
+                            "TotalAssetBalance of {:?}: {:#?}",
+                            asset_id, total_asset_balance
+                        );
+                        Ok(())
+                    })?;
                 }
             },
         }
@@ -1,15 +1,20 @@
+use std::marker::PhantomData;
+
 use anyhow::Result;
+use serde::Serialize;
 use structopt::StructOpt;
 
 use crate::{
+    app::{
+        output::{AssetLedgerOutput, MinerLedgerOutput, OutputFormat},
+        tx::Submitter,
+    },
     runtime::{
-        primitives::{AccountId, AssetId, BlockNumber},
-        xpallets::xmining_asset::{
-            AssetLedgersStoreExt, ClaimCallExt, ClaimEventExt, MinerLedgersStoreExt,
-        },
-        ChainXSigner,
+        primitives::{AccountId, AssetId},
+        xpallets::xmining_asset::{AssetLedgersStoreExt, ClaimCall, MinerLedgersStoreExt},
+        ChainXRuntime,
     },
-    utils::{block_hash, build_client, parse_account},
+    utils::{parse_account, AtBlock},
 };
 
 /// XMingAsset
@@ -29,49 +34,77 @@ pub enum Storage {
     AssetLedgers {
         #[structopt(index = 1, long)]
         asset_id: AssetId,
-        #[structopt(long)]
-        block_number: Option<BlockNumber>,
+        #[structopt(flatten)]
+        at: AtBlock,
     },
     MinerLedgers {
         #[structopt(index = 1, long, parse(try_from_str = parse_account))]
         account_id: AccountId,
         #[structopt(index = 2, long)]
         asset_id: AssetId,
-        #[structopt(long)]
-        block_number: Option<BlockNumber>,
+        #[structopt(flatten)]
+        at: AtBlock,
     },
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AssetLedgersOutput {
+    asset_id: AssetId,
+    asset_ledger: AssetLedgerOutput,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MinerLedgersOutput {
+    who: AccountId,
+    asset_id: AssetId,
+    miner_ledger: MinerLedgerOutput,
+}
+
 impl XMingAsset {
-    pub async fn run(self, url: String, signer: ChainXSigner) -> Result<()> {
-        let client = build_client(url).await?;
+    pub async fn run(self, submitter: Submitter, output: OutputFormat) -> Result<()> {
+        let client = submitter.client().await?;
 
         match self {
             Self::Claim { asset_id } => {
-                let result = client.claim_and_watch(&signer, asset_id).await?;
-                if let Some(event) = result.claim()? {
-                    println!("XMingAsset claim success: value: {:?}", event.amount);
-                } else {
-                    println!("Failed to find XMiningAsset::Claim Event");
-                }
+                let call = ClaimCall::<ChainXRuntime> {
+                    _runtime: PhantomData,
+                    target: asset_id,
+                };
+                submitter.submit_and_print(&client, call).await?;
             }
             Self::Storage(storage) => match storage {
-                Storage::AssetLedgers {
-                    asset_id,
-                    block_number,
-                } => {
-                    let at = block_hash(&client, block_number).await?;
+                Storage::AssetLedgers { asset_id, at } => {
+                    let rpc = submitter.rpc().await?;
+                    let at = at.hash(&rpc, submitter.finality()).await?;
                     let asset_ledgers = client.asset_ledgers(asset_id, at).await?;
-                    println!("AssetLedgers of {:?}: {:#?}", asset_id, asset_ledgers);
+                    let asset_ledgers_output = AssetLedgersOutput {
+                        asset_id,
+                        asset_ledger: asset_ledgers.clone().into(),
+                    };
+                    output.print(&asset_ledgers_output, |_| {
+                        println!("AssetLedgers of {:?}: {:#?}", asset_id, asset_ledgers);
+                        Ok(())
+                    })?;
                 }
                 Storage::MinerLedgers {
                     account_id,
                     asset_id,
-                    block_number,
+                    at,
                 } => {
-                    let at = block_hash(&client, block_number).await?;
+                    let rpc = submitter.rpc().await?;
+                    let at = at.hash(&rpc, submitter.finality()).await?;
                     let miner_ledgers = client.miner_ledgers(&account_id, asset_id, at).await?;
-                    println!("MinerLedgers of {:?}: {:#?}", asset_id, miner_ledgers);
+                    let miner_ledgers_output = MinerLedgersOutput {
+                        who: account_id,
+                        asset_id,
+                        miner_ledger: miner_ledgers.clone().into(),
+                    };
+                    output.print(&miner_ledgers_output, |_| {
+                        println!("MinerLedgers of {:?}: {:#?}", asset_id, miner_ledgers);
+                        Ok(())
+                    })?;
                 }
             },
         }
@@ -1,19 +1,34 @@
-use anyhow::Result;
+use std::{collections::BTreeMap, marker::PhantomData};
+
+use anyhow::{anyhow, Result};
+use chrono::{Duration, SecondsFormat, Utc};
+use codec::Decode;
+use serde::Serialize;
 use structopt::StructOpt;
-use subxt::system::AccountStoreExt;
+use subxt::{system::AccountStoreExt, Event, ExtrinsicSuccess};
 
 use crate::{
+    app::{
+        output::{
+            amounts, AccountInfoOutput, Amount, ExtrinsicOutput, NominatorLedgerOutput,
+            OutputFormat, ValidatorLedgerOutput, ValidatorProfileOutput,
+        },
+        tx::Submitter,
+    },
+    frame::utility::BatchAllCall,
     rpc::Rpc,
     runtime::{
         primitives::{AccountId, Balance, BlockNumber},
         xpallets::xstaking::{
-            BondCallExt, ChillCallExt, LocksStoreExt, NominationsStoreExt, RebondCallExt,
-            RegisterCallExt, SetValidatorCountCallExt, UnbondCallExt, ValidateCallExt,
-            ValidatorLedgersStoreExt, ValidatorsStoreExt,
+            BondCall, ChillCall, ClaimCall, ClaimedEvent, LockedType, LocksStoreExt,
+            NominationsStoreExt, RebondCall, RegisterCall, SetValidatorCountCall, UnbondCall,
+            UnbondedIndex, UnlockUnbondedWithdrawalCall, ValidateCall, ValidatorLedgersStoreExt,
+            ValidatorsStoreExt,
         },
-        ChainXSigner,
+        ChainXClient, ChainXRuntime,
     },
-    utils::{block_hash, build_client, parse_account},
+    units::{parse_pcx, PCX},
+    utils::{parse_account, AtBlock, Finality},
 };
 
 /// XStaking
@@ -25,19 +40,19 @@ pub enum XStaking {
         #[structopt(index = 1, long)]
         nickname: String,
         /// Initial validator bond
-        #[structopt(index = 2, long)]
+        #[structopt(index = 2, long, parse(try_from_str = parse_pcx))]
         initial_bond: Balance,
     },
     Bond {
         #[structopt(index = 1, long, parse(try_from_str = parse_account))]
         target: AccountId,
-        #[structopt(index = 2, long)]
+        #[structopt(index = 2, long, parse(try_from_str = parse_pcx))]
         value: Balance,
     },
     Unbond {
         #[structopt(index = 1, long, parse(try_from_str = parse_account))]
         target: AccountId,
-        #[structopt(index = 2, long)]
+        #[structopt(index = 2, long, parse(try_from_str = parse_pcx))]
         value: Balance,
     },
     Rebond {
@@ -45,9 +60,22 @@ pub enum XStaking {
         from: AccountId,
         #[structopt(index = 2, long, parse(try_from_str = parse_account))]
         to: AccountId,
-        #[structopt(index = 3, long)]
+        #[structopt(index = 3, long, parse(try_from_str = parse_pcx))]
         value: Balance,
     },
+    /// Claim the staking dividend from a validator.
+    Claim {
+        #[structopt(index = 1, long, parse(try_from_str = parse_account))]
+        target: AccountId,
+    },
+    /// Claim the staking dividends from all the nominated validators in one batch.
+    ClaimAll {
+        /// Validators whose dividend is below the threshold are skipped.
+        #[structopt(long, default_value = "0", parse(try_from_str = parse_pcx))]
+        threshold: Balance,
+    },
+    /// Withdraw all the matured unbonded chunks in one batch.
+    WithdrawUnbonded,
     Validate,
     Chill,
     SetValidatorCount {
@@ -57,20 +85,25 @@ pub enum XStaking {
     GetDividend {
         #[structopt(index = 1, long, parse(try_from_str = parse_account))]
         who: AccountId,
-        #[structopt(long)]
-        block_number: Option<BlockNumber>,
+        #[structopt(flatten)]
+        at: AtBlock,
     },
     GetNomination {
         #[structopt(index = 1, long, parse(try_from_str = parse_account))]
         who: AccountId,
-        #[structopt(long)]
-        block_number: Option<BlockNumber>,
+        #[structopt(flatten)]
+        at: AtBlock,
+    },
+    /// Show the unbonded chunks and their estimated unlock time.
+    Unbonded {
+        #[structopt(index = 1, long, parse(try_from_str = parse_account))]
+        who: AccountId,
     },
     CheckStaker {
         #[structopt(index = 1, long, parse(try_from_str = parse_account))]
         who: AccountId,
-        #[structopt(long)]
-        block_number: Option<BlockNumber>,
+        #[structopt(flatten)]
+        at: AtBlock,
     },
     Storage(Storage),
 }
@@ -80,139 +113,501 @@ pub enum Storage {
     Validators {
         #[structopt(index = 1, long, parse(try_from_str = parse_account))]
         validator_id: AccountId,
-        #[structopt(long)]
-        block_number: Option<BlockNumber>,
+        #[structopt(flatten)]
+        at: AtBlock,
     },
     ValidatorLedgers {
         #[structopt(index = 1, long, parse(try_from_str = parse_account))]
         validator_id: AccountId,
-        #[structopt(long)]
-        block_number: Option<BlockNumber>,
+        #[structopt(flatten)]
+        at: AtBlock,
     },
     Nominations {
         #[structopt(index = 1, long, parse(try_from_str = parse_account))]
         nominator: AccountId,
         #[structopt(index = 2, long, parse(try_from_str = parse_account))]
         nominatee: AccountId,
-        #[structopt(long)]
-        block_number: Option<BlockNumber>,
+        #[structopt(flatten)]
+        at: AtBlock,
     },
     Locks {
         #[structopt(index = 1, long, parse(try_from_str = parse_account))]
         staker: AccountId,
-        #[structopt(long)]
-        block_number: Option<BlockNumber>,
+        #[structopt(flatten)]
+        at: AtBlock,
     },
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LocksOutput {
+    who: AccountId,
+    locks: BTreeMap<LockedType, Amount>,
+    total_locked: Amount,
+}
+
+impl LocksOutput {
+    fn new(who: AccountId, locks: BTreeMap<LockedType, Balance>) -> Self {
+        let total_locked = locks.values().sum::<Balance>();
+        Self {
+            who,
+            locks: amounts(locks),
+            total_locked: total_locked.into(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NominationsOutput {
+    who: AccountId,
+    nominations: BTreeMap<AccountId, NominatorLedgerOutput>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CheckStakerOutput {
+    nominations: NominationsOutput,
+    locks: LocksOutput,
+    account_info: AccountInfoOutput,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ClaimOutput {
+    claimed: BTreeMap<AccountId, Amount>,
+    total_claimed: Amount,
+    skipped: BTreeMap<AccountId, Amount>,
+    #[serde(flatten)]
+    result: Option<ExtrinsicOutput>,
+}
+
+impl ClaimOutput {
+    /// Collects the claimed dividends from the `XStaking::Claimed` events.
+    async fn new(
+        submitter: &Submitter,
+        result: Option<&ExtrinsicSuccess<ChainXRuntime>>,
+        skipped: BTreeMap<AccountId, Balance>,
+    ) -> Result<Self> {
+        let mut claimed = BTreeMap::new();
+        for event in result.iter().flat_map(|result| &result.events) {
+            if event.module == ClaimedEvent::<ChainXRuntime>::MODULE
+                && event.variant == ClaimedEvent::<ChainXRuntime>::EVENT
+            {
+                let event = ClaimedEvent::<ChainXRuntime>::decode(&mut event.data.as_slice())?;
+                *claimed.entry(event.validator).or_default() += event.dividend;
+            }
+        }
+        let total_claimed = claimed.values().sum::<Balance>();
+        let result = match result {
+            Some(result) => Some(submitter.extrinsic_output(result).await?),
+            None => None,
+        };
+        Ok(Self {
+            claimed: amounts(claimed),
+            total_claimed: total_claimed.into(),
+            skipped: amounts(skipped),
+            result,
+        })
+    }
+
+    fn print_text(&self) {
+        for (validator, dividend) in &self.claimed {
+            println!("Claimed {} from {}", PCX.format(dividend.0), validator);
+        }
+        for (validator, dividend) in &self.skipped {
+            println!("Skipped {} from {}", PCX.format(dividend.0), validator);
+        }
+        println!("Total claimed: {}", PCX.format(self.total_claimed.0));
+        if let Some(ref result) = self.result {
+            println!("Included in block {:?}", result.block);
+            result.print_finality();
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UnbondedChunkOutput {
+    nominee: AccountId,
+    index: UnbondedIndex,
+    value: Amount,
+    locked_until: BlockNumber,
+    remaining_blocks: BlockNumber,
+    /// Estimated unlock time in RFC 3339.
+    unlock_time: String,
+}
+
+impl UnbondedChunkOutput {
+    /// The chunk can be withdrawn in the next block once the head reaches `locked_until`.
+    fn is_matured(&self) -> bool {
+        self.remaining_blocks == 0
+    }
+
+    fn print_text(&self) {
+        println!(
+            "{} #{}: {}, locked until block {} ({} blocks remaining, ~{})",
+            self.nominee,
+            self.index,
+            PCX.format(self.value.0),
+            self.locked_until,
+            self.remaining_blocks,
+            self.unlock_time
+        );
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UnbondedScheduleOutput {
+    who: AccountId,
+    head: BlockNumber,
+    /// Expected block time in milliseconds.
+    block_time: u64,
+    chunks: Vec<UnbondedChunkOutput>,
+}
+
+impl UnbondedScheduleOutput {
+    async fn fetch(
+        client: &ChainXClient,
+        rpc: &Rpc,
+        who: AccountId,
+        finality: Finality,
+    ) -> Result<Self> {
+        // The head is pinned so that all the queries are at the same block.
+        let head_hash = match finality {
+            Finality::Best => client.block_hash(None).await?,
+            Finality::Finalized => Some(client.finalized_head().await?),
+        };
+        let head = client
+            .header(head_hash)
+            .await?
+            .ok_or_else(|| anyhow!("Header of the best block not found"))?
+            .number;
+        // A block is expected every two minimum periods of the timestamp.
+        let block_time = 2 * rpc
+            .metadata(head_hash)
+            .await?
+            .constant::<u64>("Timestamp", "MinimumPeriod")?;
+
+        let now = Utc::now();
+        let mut chunks = Vec::new();
+        for (nominee, ledger) in rpc.get_nominations_rpc(who.clone(), head_hash).await? {
+            for (index, chunk) in ledger.unbonded_chunks.into_iter().enumerate() {
+                let remaining_blocks = chunk.locked_until.saturating_sub(head);
+                let unlock_time =
+                    now + Duration::milliseconds((u64::from(remaining_blocks) * block_time) as i64);
+                chunks.push(UnbondedChunkOutput {
+                    nominee: nominee.clone(),
+                    index: index as UnbondedIndex,
+                    value: chunk.value.into(),
+                    locked_until: chunk.locked_until,
+                    remaining_blocks,
+                    unlock_time: unlock_time.to_rfc3339_opts(SecondsFormat::Secs, true),
+                });
+            }
+        }
+
+        Ok(Self {
+            who,
+            head,
+            block_time,
+            chunks,
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WithdrawUnbondedOutput {
+    withdrawn: Vec<UnbondedChunkOutput>,
+    total: Amount,
+    #[serde(flatten)]
+    result: ExtrinsicOutput,
+}
+
 impl XStaking {
-    pub async fn run(self, url: String, signer: ChainXSigner) -> Result<()> {
-        let client = build_client(url.clone()).await?;
+    pub async fn run(self, submitter: Submitter, output: OutputFormat) -> Result<()> {
+        let client = submitter.client().await?;
 
         match self {
             Self::Register {
                 nickname,
                 initial_bond,
             } => {
-                let result = client
-                    .register_and_watch(&signer, nickname.as_bytes().to_vec(), initial_bond)
-                    .await?;
-                println!("register result:{:#?}", result);
+                let call = RegisterCall::<ChainXRuntime> {
+                    _runtime: PhantomData,
+                    validator_nickname: nickname.as_bytes().to_vec(),
+                    initial_bond,
+                };
+                submitter.submit_and_print(&client, call).await?;
             }
             Self::Bond { target, value } => {
-                let result = client
-                    .bond_and_watch(&signer, &target.into(), value)
-                    .await?;
-                println!("bond result:{:#?}", result);
+                let call = BondCall::<ChainXRuntime> {
+                    target: &target.into(),
+                    value,
+                };
+                submitter.submit_and_print(&client, call).await?;
             }
             Self::Unbond { target, value } => {
-                let result = client
-                    .unbond_and_watch(&signer, &target.into(), value)
-                    .await?;
-                println!("unbond result:{:#?}", result);
+                let call = UnbondCall::<ChainXRuntime> {
+                    target: &target.into(),
+                    value,
+                };
+                submitter.submit_and_print(&client, call).await?;
             }
             Self::Rebond { from, to, value } => {
-                let result = client
-                    .rebond_and_watch(&signer, &from.into(), &to.into(), value)
-                    .await?;
-                println!("rebond result:{:#?}", result);
+                let call = RebondCall::<ChainXRuntime> {
+                    from: &from.into(),
+                    to: &to.into(),
+                    value,
+                };
+                submitter.submit_and_print(&client, call).await?;
+            }
+            Self::Claim { target } => {
+                let call = ClaimCall::<ChainXRuntime> {
+                    target: &target.into(),
+                };
+                if let Some(result) = submitter.submit(&client, call).await? {
+                    let claim =
+                        ClaimOutput::new(&submitter, Some(&result), BTreeMap::new()).await?;
+                    output.print(&claim, |claim| {
+                        claim.print_text();
+                        Ok(())
+                    })?;
+                }
+            }
+            Self::ClaimAll { threshold } => {
+                let rpc = submitter.rpc().await?;
+                let dividends = rpc.get_staking_dividend(submitter.sender(), None).await?;
+                let (claimable, skipped): (BTreeMap<_, _>, BTreeMap<_, _>) = dividends
+                    .into_iter()
+                    .partition(|(_, dividend)| *dividend > 0 && *dividend >= threshold);
+
+                if claimable.is_empty() {
+                    let claim = ClaimOutput::new(&submitter, None, skipped).await?;
+                    output.print(&claim, |claim| {
+                        claim.print_text();
+                        println!(
+                            "No dividend reaches the threshold {}",
+                            PCX.format(threshold)
+                        );
+                        Ok(())
+                    })?;
+                    return Ok(());
+                }
+
+                let calls = claimable
+                    .keys()
+                    .map(|validator| {
+                        client.encode(ClaimCall::<ChainXRuntime> {
+                            target: &validator.clone().into(),
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let call = BatchAllCall::<ChainXRuntime> {
+                    _runtime: PhantomData,
+                    calls: &calls,
+                };
+                if let Some(result) = submitter.submit(&client, call).await? {
+                    let claim = ClaimOutput::new(&submitter, Some(&result), skipped).await?;
+                    output.print(&claim, |claim| {
+                        claim.print_text();
+                        Ok(())
+                    })?;
+                }
+            }
+            Self::WithdrawUnbonded => {
+                let rpc = submitter.rpc().await?;
+                // The withdrawals are checked against the best block they'll be applied on.
+                let schedule = UnbondedScheduleOutput::fetch(
+                    &client,
+                    &rpc,
+                    submitter.sender(),
+                    Finality::Best,
+                )
+                .await?;
+                // The chunks of a nominee are withdrawn from the last one, so that the removal
+                // won't change the indices of the rest.
+                let matured = schedule
+                    .chunks
+                    .into_iter()
+                    .filter(UnbondedChunkOutput::is_matured)
+                    .rev()
+                    .collect::<Vec<_>>();
+                if matured.is_empty() {
+                    return Err(anyhow!(
+                        "No matured unbonded chunk of {} at block {}",
+                        schedule.who,
+                        schedule.head
+                    ));
+                }
+
+                let calls = matured
+                    .iter()
+                    .map(|chunk| {
+                        client.encode(UnlockUnbondedWithdrawalCall::<ChainXRuntime> {
+                            target: &chunk.nominee.clone().into(),
+                            unbonded_index: chunk.index,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let call = BatchAllCall::<ChainXRuntime> {
+                    _runtime: PhantomData,
+                    calls: &calls,
+                };
+                if let Some(result) = submitter.submit(&client, call).await? {
+                    let total = matured.iter().map(|chunk| chunk.value.0).sum::<Balance>();
+                    let withdraw = WithdrawUnbondedOutput {
+                        withdrawn: matured,
+                        total: total.into(),
+                        result: submitter.extrinsic_output(&result).await?,
+                    };
+                    output.print(&withdraw, |withdraw| {
+                        for chunk in &withdraw.withdrawn {
+                            chunk.print_text();
+                        }
+                        println!("Total withdrawn: {}", PCX.format(withdraw.total.0));
+                        println!("Included in block {:?}", withdraw.result.block);
+                        Ok(())
+                    })?;
+                }
             }
             Self::Validate => {
-                let result = client.validate_and_watch(&signer).await?;
-                println!("validate result:{:#?}", result);
+                let call = ValidateCall::<ChainXRuntime> {
+                    _runtime: PhantomData,
+                };
+                submitter.submit_and_print(&client, call).await?;
             }
             Self::Chill => {
-                let result = client.chill_and_watch(&signer).await?;
-                println!("chill result:{:#?}", result);
+                let call = ChillCall::<ChainXRuntime> {
+                    _runtime: PhantomData,
+                };
+                submitter.submit_and_print(&client, call).await?;
             }
             Self::SetValidatorCount { new } => {
-                let result = client.set_validator_count_and_watch(&signer, new).await?;
-                println!("set_validator_count result:{:#?}", result);
+                let call = SetValidatorCountCall::<ChainXRuntime> {
+                    _runtime: PhantomData,
+                    new,
+                };
+                submitter.submit_and_print(&client, call).await?;
             }
-            Self::GetDividend { who, block_number } => {
-                let rpc = Rpc::new(url).await?;
-                let at = block_hash(&client, block_number).await?;
+            Self::GetDividend { who, at } => {
+                let rpc = submitter.rpc().await?;
+                let at = at.hash(&rpc, submitter.finality()).await?;
                 let dividend = rpc.get_staking_dividend(who.clone(), at).await?;
-                println!("Staking dividend of {:?}: {:#?}", who, dividend);
+                output.print(&amounts(dividend.clone()), |_| {
+                    println!("Staking dividend of {:?}: {:#?}", who, dividend);
+                    Ok(())
+                })?;
             }
-            Self::CheckStaker { who, block_number } => {
-                let rpc = Rpc::new(url).await?;
-                let at = block_hash(&client, block_number).await?;
+            Self::Unbonded { who } => {
+                let rpc = submitter.rpc().await?;
+                let schedule =
+                    UnbondedScheduleOutput::fetch(&client, &rpc, who, submitter.finality()).await?;
+                output.print(&schedule, |schedule| {
+                    println!(
+                        "Unbonded chunks of {} at block {}:",
+                        schedule.who, schedule.head
+                    );
+                    for chunk in &schedule.chunks {
+                        chunk.print_text();
+                    }
+                    Ok(())
+                })?;
+            }
+            Self::CheckStaker { who, at } => {
+                let rpc = submitter.rpc().await?;
+                let at = at.hash(&rpc, submitter.finality()).await?;
 
                 let nominations = rpc.get_nominations_rpc(who.clone(), at).await?;
-                println!("Nominations of {:?}: {:#?}", who, nominations);
-
                 let locks = client.locks(&who, at).await?;
-                let total_locked = locks.values().sum::<u128>();
-                println!("Locks for {:?}", who);
-                println!("Details: {:#?}", locks);
-                println!("total locked in Staking: {}", total_locked);
-
                 let account_info = client.account(&who, at).await?;
-                println!("AccountInfo of {:?}: {:#?}", who, account_info);
+
+                let check_staker_output = CheckStakerOutput {
+                    nominations: NominationsOutput {
+                        who: who.clone(),
+                        nominations: nominations
+                            .clone()
+                            .into_iter()
+                            .map(|(nominee, ledger)| (nominee, ledger.into()))
+                            .collect(),
+                    },
+                    locks: LocksOutput::new(who.clone(), locks.clone()),
+                    account_info: AccountInfoOutput::new(who.clone(), account_info.clone()),
+                };
+                output.print(&check_staker_output, |_| {
+                    println!("Nominations of {:?}: {:#?}", who, nominations);
+
+                    let total_locked = locks.values().sum::<u128>();
+                    println!("Locks for {:?}", who);
+                    println!("Details: {:#?}", locks);
+                    println!("total locked in Staking: {}", PCX.format(total_locked));
+
+                    println!("AccountInfo of {:?}: {:#?}", who, account_info);
+                    Ok(())
+                })?;
             }
-            Self::GetNomination { who, block_number } => {
-                let rpc = Rpc::new(url).await?;
-                let at = block_hash(&client, block_number).await?;
+            Self::GetNomination { who, at } => {
+                let rpc = submitter.rpc().await?;
+                let at = at.hash(&rpc, submitter.finality()).await?;
                 let nominations = rpc.get_nominations_rpc(who.clone(), at).await?;
-                println!("Nominations of {:?}: {:#?}", who, nominations);
+                let nominations_output = NominationsOutput {
+                    who: who.clone(),
+                    nominations: nominations
+                        .clone()
+                        .into_iter()
+                        .map(|(nominee, ledger)| (nominee, ledger.into()))
+                        .collect(),
+                };
+                output.print(&nominations_output, |_| {
+                    println!("Nominations of {:?}: {:#?}", who, nominations);
+                    Ok(())
+                })?;
             }
             Self::Storage(storage) => match storage {
-                Storage::Validators {
-                    validator_id,
-                    block_number,
-                } => {
-                    let at = block_hash(&client, block_number).await?;
+                Storage::Validators { validator_id, at } => {
+                    let rpc = submitter.rpc().await?;
+                    let at = at.hash(&rpc, submitter.finality()).await?;
                     let profile = client.validators(&validator_id, at).await?;
-                    println!("{:?}: {:#?}", validator_id, profile);
+                    output.print(&ValidatorProfileOutput::from(profile.clone()), |_| {
+                        println!("{:?}: {:#?}", validator_id, profile);
+                        Ok(())
+                    })?;
                 }
-                Storage::ValidatorLedgers {
-                    validator_id,
-                    block_number,
-                } => {
-                    let at = block_hash(&client, block_number).await?;
+                Storage::ValidatorLedgers { validator_id, at } => {
+                    let rpc = submitter.rpc().await?;
+                    let at = at.hash(&rpc, submitter.finality()).await?;
                     let ledgers = client.validator_ledgers(&validator_id, at).await?;
-                    println!("{:?}: {:#?}", validator_id, ledgers);
+                    output.print(&ValidatorLedgerOutput::from(ledgers.clone()), |_| {
+                        println!("{:?}: {:#?}", validator_id, ledgers);
+                        Ok(())
+                    })?;
                 }
                 Storage::Nominations {
                     nominator,
                     nominatee,
-                    block_number,
+                    at,
                 } => {
-                    let at = block_hash(&client, block_number).await?;
+                    let rpc = submitter.rpc().await?;
+                    let at = at.hash(&rpc, submitter.finality()).await?;
                     let ledgers = client.nominations(&nominator, &nominatee, at).await?;
-                    println!("{:?} => {:?}: {:#?}", nominator, nominatee, ledgers);
+                    output.print(&NominatorLedgerOutput::from(ledgers.clone()), |_| {
+                        println!("{:?} => {:?}: {:#?}", nominator, nominatee, ledgers);
+                        Ok(())
+                    })?;
                 }
-                Storage::Locks {
-                    staker,
-                    block_number,
-                } => {
-                    let at = block_hash(&client, block_number).await?;
+                Storage::Locks { staker, at } => {
+                    let rpc = submitter.rpc().await?;
+                    let at = at.hash(&rpc, submitter.finality()).await?;
                     let locks = client.locks(&staker, at).await?;
-                    let total_locked = locks.values().sum::<u128>();
-                    println!("Locks for {:?}", staker);
-                    println!("Details: {:#?}", locks);
-                    println!("total locked in Staking: {}", total_locked);
+                    output.print(&LocksOutput::new(staker.clone(), locks.clone()), |_| {
+                        let total_locked = locks.values().sum::<u128>();
+                        println!("Locks for {:?}", staker);
+                        println!("Details: {:#?}", locks);
+                        println!("total locked in Staking: {}", PCX.format(total_locked));
+                        Ok(())
+                    })?;
                 }
             },
         }
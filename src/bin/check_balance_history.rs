@@ -2,25 +2,22 @@
 
 use std::cmp::Ordering;
 
-use anyhow::Result;
-use sp_runtime::generic::{Block, SignedBlock};
+use anyhow::{anyhow, Result};
 use structopt::StructOpt;
-use subxt::system::{AccountStoreExt, System};
 
 use chainx_cli::{
-    block_hash, build_client, parse_account,
-    runtime::{
-        primitives::{AccountId, BlockNumber},
-        ChainXClient, ChainXRuntime,
-    },
+    parse_account,
+    rpc::Rpc,
+    runtime::primitives::{AccountId, BlockNumber},
+    units::PCX,
 };
 
 #[derive(StructOpt, Debug)]
 #[structopt(author, about, no_version)]
 struct App {
-    /// The websocket url of ChainX node.
-    #[structopt(long, default_value = "ws://127.0.0.1:8087")]
-    pub url: String,
+    /// The websocket url of ChainX node, can be given multiple times for failover.
+    #[structopt(long, default_value = "ws://127.0.0.1:8087", number_of_values = 1)]
+    pub url: Vec<String>,
 
     /// Account
     #[structopt(short, long, parse(try_from_str = parse_account))]
@@ -41,16 +38,6 @@ struct App {
     pub ss58_prefix: sp_core::crypto::Ss58AddressFormat,
 }
 
-pub type ChainBlock<T> = SignedBlock<Block<<T as System>::Header, <T as System>::Extrinsic>>;
-
-async fn latest_block_number(client: &ChainXClient) -> Result<BlockNumber> {
-    let latest_block: ChainBlock<ChainXRuntime> = client
-        .block(None::<<ChainXRuntime as System>::Hash>)
-        .await?
-        .expect("Failed to fetch latest block");
-    Ok(latest_block.block.header.number)
-}
-
 #[async_std::main]
 async fn main() -> Result<()> {
     env_logger::init();
@@ -59,22 +46,26 @@ async fn main() -> Result<()> {
 
     sp_core::crypto::set_default_ss58_version(sp_core::crypto::Ss58AddressFormat::ChainXAccount);
 
-    let client = build_client(app.url.clone()).await?;
+    let rpc = Rpc::new(&app.url).await?;
+    let metadata = rpc.metadata(None).await?;
 
     let who = app.who;
     let start_block = app.start_block.unwrap_or(0);
     let end_block = if let Some(block_number) = app.end_block {
         block_number
     } else {
-        latest_block_number(&client).await?
+        rpc.header(None)
+            .await?
+            .ok_or_else(|| anyhow!("Failed to fetch latest block"))?
+            .number
     };
 
     let mut last_free = 0;
     let mut latest_diff = 0;
 
     for blk in start_block..=end_block {
-        let at = block_hash(&client, Some(blk)).await?;
-        let account_info = client.account(&who, at).await?;
+        let at = rpc.block_hash(blk).await?;
+        let account_info = rpc.get_account_info(&metadata, &who, at).await?;
         let new_free = account_info.data.free;
         if new_free != last_free {
             let (sign, diff) = match new_free.cmp(&last_free) {
@@ -87,18 +78,18 @@ async fn main() -> Result<()> {
                 println!(
                     "{:>14}, free {}, new diff{}: {}",
                     format!("Block#{}", blk),
-                    new_free,
+                    PCX.format(new_free),
                     sign,
-                    diff
+                    PCX.format(diff)
                 );
                 latest_diff = diff;
             } else {
                 println!(
                     "{:>14}, free {},     diff{}: {}",
                     format!("Block#{}", blk),
-                    new_free,
+                    PCX.format(new_free),
                     sign,
-                    diff
+                    PCX.format(diff)
                 );
             }
 
@@ -10,18 +10,14 @@
 use anyhow::Result;
 use structopt::StructOpt;
 
-use chainx_cli::{
-    block_hash, build_client,
-    rpc::Rpc,
-    runtime::{primitives::BlockNumber, xpallets::xstaking::LocksStoreExt},
-};
+use chainx_cli::{rpc::Rpc, runtime::primitives::BlockNumber, units::PCX};
 
 #[derive(StructOpt, Debug)]
 #[structopt(author, about, no_version)]
 struct App {
-    /// The websocket url of ChainX node.
-    #[structopt(long, default_value = "ws://127.0.0.1:8087")]
-    pub url: String,
+    /// The websocket url of ChainX node, can be given multiple times for failover.
+    #[structopt(long, default_value = "ws://127.0.0.1:8087", number_of_values = 1)]
+    pub url: Vec<String>,
 
     #[structopt(long)]
     pub block_number: Option<BlockNumber>,
@@ -41,17 +37,16 @@ async fn main() -> Result<()> {
 
     sp_core::crypto::set_default_ss58_version(app.ss58_prefix);
 
-    let client = build_client(app.url.clone()).await?;
-    let at = block_hash(&client, app.block_number).await?;
+    let rpc = Rpc::new(&app.url).await?;
+    let at = match app.block_number {
+        Some(number) => rpc.block_hash(number).await?,
+        None => Some(rpc.best_hash().await?),
+    };
 
-    let rpc = Rpc::new(app.url.clone()).await?;
+    println!("Running at Block #{:?}", at.unwrap_or_default());
 
-    println!(
-        "Running at Block #{:?}",
-        at.unwrap_or(client.block_hash(None).await?.unwrap_or_default())
-    );
-
-    let genesis_hash = *client.genesis();
+    let genesis_hash = rpc.genesis_hash().await?;
+    let genesis_metadata = rpc.metadata(Some(genesis_hash)).await?;
 
     let accounts_info = rpc.get_accounts_info(at).await?;
 
@@ -60,7 +55,9 @@ async fn main() -> Result<()> {
     let mut never_claimed = Vec::with_capacity(accounts_info.len());
 
     for (who, info) in accounts_info {
-        let locks = client.locks(&who, Some(genesis_hash)).await?;
+        let locks = rpc
+            .get_locks(&genesis_metadata, &who, Some(genesis_hash))
+            .await?;
         let total_locked = locks.values().sum::<u128>();
         if total_locked > 0 && info.nonce == 0 {
             never_claimed.push((who, total_locked));
@@ -70,14 +67,11 @@ async fn main() -> Result<()> {
     never_claimed.sort_unstable_by_key(|k| k.1);
     never_claimed.reverse();
 
-    let pretty_balance =
-        |balance: u128| format!("{}.{}", balance / 100_000_000, balance % 100_000_000);
-
     for (who, locked) in never_claimed {
         println!(
             "who {} locked {}, but never claimed",
             who,
-            pretty_balance(locked)
+            PCX.format(locked)
         );
     }
     Ok(())
@@ -3,21 +3,18 @@
 
 use anyhow::Result;
 use chainx_cli::{
-    block_hash, build_client,
     rpc::Rpc,
-    runtime::{
-        primitives::BlockNumber,
-        xpallets::xstaking::{LockedType, LocksStoreExt},
-    },
+    runtime::{primitives::BlockNumber, xpallets::xstaking::LockedType},
+    units::PCX,
 };
 use structopt::StructOpt;
 
 #[derive(StructOpt, Debug)]
 #[structopt(author, about, no_version)]
 struct App {
-    /// The websocket url of ChainX node.
-    #[structopt(long, default_value = "ws://127.0.0.1:8087")]
-    pub url: String,
+    /// The websocket url of ChainX node, can be given multiple times for failover.
+    #[structopt(long, default_value = "ws://127.0.0.1:8087", number_of_values = 1)]
+    pub url: Vec<String>,
 
     #[structopt(long)]
     pub block_number: Option<BlockNumber>,
@@ -37,21 +34,20 @@ async fn main() -> Result<()> {
 
     sp_core::crypto::set_default_ss58_version(app.ss58_prefix);
 
-    let client = build_client(app.url.clone()).await?;
-    let at = block_hash(&client, app.block_number).await?;
+    let rpc = Rpc::new(&app.url).await?;
+    let at = match app.block_number {
+        Some(number) => rpc.block_hash(number).await?,
+        None => Some(rpc.best_hash().await?),
+    };
 
-    let rpc = Rpc::new(app.url.clone()).await?;
-
-    println!(
-        "Running at Block #{:?}",
-        at.unwrap_or(client.block_hash(None).await?.unwrap_or_default())
-    );
+    println!("Running at Block #{:?}", at.unwrap_or_default());
+    let metadata = rpc.metadata(at).await?;
     let accounts_info = rpc.get_accounts_info(at).await?;
 
     let mut total_negative = 0;
     let mut total_unlocking = 0;
     for (who, info) in accounts_info {
-        let mut locks = client.locks(&who, at).await?;
+        let mut locks = rpc.get_locks(&metadata, &who, at).await?;
         let total_locked = locks.values().sum::<u128>();
         total_unlocking += *locks.entry(LockedType::BondedWithdrawal).or_default();
         let account_data = info.data;
@@ -71,13 +67,13 @@ async fn main() -> Result<()> {
             println!(
                 "[ERROR] {} has negative usable: -{}",
                 who,
-                account_data.misc_frozen - account_data.free,
+                PCX.format(account_data.misc_frozen - account_data.free),
             );
         }
     }
 
-    println!("Total negative: {}", total_negative);
-    println!("Total unlockings: {}", total_unlocking);
+    println!("Total negative: {}", PCX.format(total_negative));
+    println!("Total unlockings: {}", PCX.format(total_unlocking));
 
     Ok(())
 }
@@ -24,9 +24,9 @@ use chainx_cli::{
 #[derive(StructOpt, Debug)]
 #[structopt(author, about, no_version)]
 struct App {
-    /// The websocket url of ChainX node.
-    #[structopt(long, default_value = "ws://127.0.0.1:8087")]
-    pub url: String,
+    /// The websocket url of ChainX node, can be given multiple times for failover.
+    #[structopt(long, default_value = "ws://127.0.0.1:8087", number_of_values = 1)]
+    pub url: Vec<String>,
 
     /// The start block of the balance history.
     #[structopt(long)]
@@ -37,6 +37,10 @@ struct App {
     /// 44 for ChainX mainnet, 42 for Substrate.
     #[structopt(long, default_value = "44")]
     pub ss58_prefix: sp_core::crypto::Ss58AddressFormat,
+
+    /// Number of the storage keys fetched in one page.
+    #[structopt(long, default_value = "1000")]
+    pub page_size: u32,
 }
 
 pub type ChainBlock<T> = SignedBlock<Block<<T as System>::Header, <T as System>::Extrinsic>>;
@@ -268,7 +272,7 @@ async fn main() -> Result<()> {
 
     let app = App::from_args();
 
-    let client = build_client(app.url.clone()).await?;
+    let client = build_client(&app.url).await?;
 
     let block_number = if let Some(number) = app.block_number {
         number
@@ -278,7 +282,7 @@ async fn main() -> Result<()> {
 
     let at = block_hash(&client, Some(block_number)).await?;
 
-    let rpc = Rpc::new(app.url).await?;
+    let rpc = Rpc::new(&app.url).await?.with_page_size(app.page_size);
     let full_params = RegenesisBuilder::new(rpc, at).build().await?;
 
     let output_filename = format!("{}_regenesis_params.json", block_number);
@@ -27,9 +27,9 @@ use chainx_cli::{
 #[derive(StructOpt, Debug)]
 #[structopt(author, about, no_version)]
 struct App {
-    /// The websocket url of ChainX node.
-    #[structopt(long, default_value = "ws://127.0.0.1:8087")]
-    pub url: String,
+    /// The websocket url of ChainX node, can be given multiple times for failover.
+    #[structopt(long, default_value = "ws://127.0.0.1:8087", number_of_values = 1)]
+    pub url: Vec<String>,
 
     /// The start block of the balance history.
     #[structopt(long)]
@@ -43,7 +43,11 @@ struct App {
     pub ss58_prefix: sp_core::crypto::Ss58AddressFormat,
 
     #[structopt(long, default_value = "100_000_000")]
-    pub min_balance: Balance
+    pub min_balance: Balance,
+
+    /// Number of the storage keys fetched in one page.
+    #[structopt(long, default_value = "1000")]
+    pub page_size: u32,
 }
 
 pub type ChainBlock<T> = SignedBlock<Block<<T as System>::Header, <T as System>::Extrinsic>>;
@@ -106,9 +110,9 @@ async fn main() -> Result<()> {
 
     sp_core::crypto::set_default_ss58_version(Ss58AddressFormat::ChainXAccount);
 
-    let client = build_client(app.url.clone()).await?;
+    let client = build_client(&app.url).await?;
 
-    let rpc = Rpc::new(app.url).await?;
+    let rpc = Rpc::new(&app.url).await?.with_page_size(app.page_size);
 
     let block_number = if let Some(number) = app.block_number {
         number
@@ -41,9 +41,9 @@ pub struct Config {
     /// Specify the path of genesis json file
     #[structopt(long)]
     genesis: PathBuf,
-    /// Specify the WebSocket url of ChainX node.
-    #[structopt(long, default_value = "ws://127.0.0.1:8087")]
-    chainx_url: String,
+    /// Specify the WebSocket url of ChainX node, can be given multiple times for failover.
+    #[structopt(long, default_value = "ws://127.0.0.1:8087", number_of_values = 1)]
+    chainx_url: Vec<String>,
 }
 
 impl Config {
@@ -116,7 +116,7 @@ async fn main() -> Result<()> {
 
     set_default_ss58_version(Ss58AddressFormat::ChainXAccount);
 
-    let rpc = Rpc::new(config.chainx_url).await?;
+    let rpc = Rpc::new(&config.chainx_url).await?;
 
     let genesis_hash = rpc.genesis_hash().await?;
     println!("Genesis Hash: {:?}", genesis_hash);
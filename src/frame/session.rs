@@ -22,6 +22,8 @@ use std::{fmt::Debug, marker::PhantomData};
 use subxt::system::{System, SystemEventsDecoder as _};
 use subxt::{module, Call, Store};
 
+use crate::metadata::compat::{CallDecl, PalletDecl, StorageDecl};
+
 /// Impls `Default::default` for some types that have a `_runtime` field of type
 /// `PhantomData` as their only field.
 macro_rules! default_impl {
@@ -71,3 +73,24 @@ pub struct NextKeysStore<'a, T: Session> {
     #[store(returns = Option<<T as Session>::Keys>)]
     pub account_id: &'a <T as Session>::ValidatorId,
 }
+
+/// The calls and storages above, checked against the runtime metadata.
+pub const PALLET: PalletDecl = PalletDecl {
+    name: "Session",
+    calls: &[CallDecl {
+        name: "set_keys",
+        args: &[("keys", "T::Keys"), ("proof", "Vec<u8>")],
+    }],
+    storage: &[
+        StorageDecl {
+            name: "Validators",
+            keys: &[],
+            value: "Vec<T::ValidatorId>",
+        },
+        StorageDecl {
+            name: "NextKeys",
+            keys: &[(None, "T::ValidatorId")],
+            value: "T::Keys",
+        },
+    ],
+};
@@ -0,0 +1,27 @@
+//! Utility support
+use codec::{Decode, Encode};
+use std::marker::PhantomData;
+use subxt::system::{System, SystemEventsDecoder as _};
+use subxt::{module, Call, Encoded, Event};
+
+/// The trait needed for this module.
+#[module]
+pub trait Utility: System {}
+
+/// Send a batch of dispatch calls and atomically execute them.
+///
+/// The whole transaction will rollback and fail if any of the calls failed.
+#[derive(Encode, Call, Debug)]
+pub struct BatchAllCall<'a, T: Utility> {
+    /// Marker for the runtime
+    pub _runtime: PhantomData<T>,
+    /// The SCALE encoded calls.
+    pub calls: &'a [Encoded],
+}
+
+/// Batch of dispatches completed fully with no error.
+#[derive(Clone, Debug, Eq, PartialEq, Event, Decode)]
+pub struct BatchCompletedEvent<T: Utility> {
+    /// Marker for the runtime
+    pub _runtime: PhantomData<T>,
+}
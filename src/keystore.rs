@@ -0,0 +1,432 @@
+//! Local keystore of `chainx-cli`.
+//!
+//! Each account is stored as `<name>.json` under the keystore directory, in the same
+//! encrypted JSON format exported by polkadot.js (PKCS8 encrypted by scrypt and
+//! xsalsa20-poly1305), so the files can also be imported back into polkadot.js.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use rand::{rngs::OsRng, RngCore};
+use serde::{de, Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sp_core::{crypto::Ss58Codec, ecdsa, ed25519, sr25519, Pair};
+use sp_runtime::{traits::IdentifyAccount, MultiSigner};
+use xsalsa20poly1305::{
+    aead::{generic_array::GenericArray, Aead, NewAead},
+    XSalsa20Poly1305,
+};
+
+use crate::runtime::{primitives::AccountId, ChainXSigner, CryptoScheme};
+
+const PKCS8_HEADER: [u8; 16] = [48, 83, 2, 1, 1, 48, 5, 6, 3, 43, 101, 112, 4, 34, 4, 32];
+const PKCS8_DIVIDER: [u8; 5] = [161, 35, 3, 33, 0];
+const SEED_LEN: usize = 32;
+const SECRET_KEY_LEN: usize = 64;
+const PUBLIC_KEY_LEN: usize = 32;
+
+const SALT_LEN: usize = 32;
+/// Salt + N + p + r.
+const SCRYPT_HEADER_LEN: usize = SALT_LEN + 4 * 3;
+const NONCE_LEN: usize = 24;
+const KEY_LEN: usize = 32;
+
+/// Default scrypt parameters used by polkadot.js.
+const SCRYPT_N: u32 = 1 << 15;
+const SCRYPT_P: u32 = 1;
+const SCRYPT_R: u32 = 8;
+
+const ENCODING_VERSION: &str = "3";
+
+/// Accepts both `"xsalsa20-poly1305"` and `["scrypt", "xsalsa20-poly1305"]`.
+fn one_or_many<'de, D>(deserializer: D) -> std::result::Result<Vec<String>, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(one) => vec![one],
+        OneOrMany::Many(many) => many,
+    })
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Encoding {
+    /// e.g., `["pkcs8", "sr25519"]`.
+    #[serde(deserialize_with = "one_or_many")]
+    pub content: Vec<String>,
+    /// e.g., `["scrypt", "xsalsa20-poly1305"]`.
+    #[serde(rename = "type", deserialize_with = "one_or_many")]
+    pub ty: Vec<String>,
+    pub version: String,
+}
+
+/// An encrypted account in the JSON format of polkadot.js.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KeyFile {
+    /// Base64 encoded encrypted PKCS8.
+    pub encoded: String,
+    pub encoding: Encoding,
+    pub address: String,
+    #[serde(default)]
+    pub meta: Map<String, Value>,
+}
+
+fn scrypt_key(password: &str, salt: &[u8], n: u32, p: u32, r: u32) -> Result<Vec<u8>> {
+    if !n.is_power_of_two() {
+        return Err(anyhow!("Invalid scrypt parameter N: {}", n));
+    }
+    let params = scrypt::ScryptParams::new(n.trailing_zeros() as u8, r, p)
+        .map_err(|_| anyhow!("Invalid scrypt parameters: N={}, p={}, r={}", n, p, r))?;
+    // polkadot.js derives 64 bytes and uses the first 32 bytes as the key.
+    let mut output = vec![0u8; 64];
+    scrypt::scrypt(password.as_bytes(), salt, &params, &mut output)
+        .map_err(|_| anyhow!("Failed to derive the key by scrypt"))?;
+    output.truncate(KEY_LEN);
+    Ok(output)
+}
+
+fn read_u32_le(bytes: &[u8]) -> u32 {
+    let mut buf = [0u8; 4];
+    buf.copy_from_slice(&bytes[..4]);
+    u32::from_le_bytes(buf)
+}
+
+/// Returns the secret key and the public key in PKCS8.
+fn decode_pkcs8(data: &[u8]) -> Result<(&[u8], &[u8])> {
+    if !data.starts_with(&PKCS8_HEADER) {
+        return Err(anyhow!("Invalid PKCS8 header"));
+    }
+    let body = &data[PKCS8_HEADER.len()..];
+    // The old format only contains the 32 bytes seed instead of the 64 bytes secret key.
+    for secret_len in [SECRET_KEY_LEN, SEED_LEN].iter().copied() {
+        let divider = body.get(secret_len..secret_len + PKCS8_DIVIDER.len());
+        if divider == Some(&PKCS8_DIVIDER[..]) {
+            // The public key is 32 bytes, except for 33 bytes of the compressed ecdsa key.
+            let public = &body[secret_len + PKCS8_DIVIDER.len()..];
+            if public.len() != PUBLIC_KEY_LEN && public.len() != PUBLIC_KEY_LEN + 1 {
+                return Err(anyhow!("Invalid PKCS8 public key"));
+            }
+            return Ok((&body[..secret_len], public));
+        }
+    }
+    Err(anyhow!("Invalid PKCS8 divider"))
+}
+
+fn encode_pkcs8(secret: &[u8], public: &[u8]) -> Vec<u8> {
+    let mut data = PKCS8_HEADER.to_vec();
+    data.extend_from_slice(secret);
+    data.extend_from_slice(&PKCS8_DIVIDER);
+    data.extend_from_slice(public);
+    data
+}
+
+fn sr25519_from_secret(secret: &[u8]) -> Result<sr25519::Pair> {
+    let pair = match secret.len() {
+        SEED_LEN => sr25519::Pair::from_seed_slice(secret),
+        // polkadot.js stores the sr25519 secret key in the ed25519 expanded format.
+        SECRET_KEY_LEN => {
+            let secret = schnorrkel::SecretKey::from_ed25519_bytes(secret)
+                .map_err(|err| anyhow!("Invalid sr25519 secret key: {}", err))?;
+            sr25519::Pair::from_seed_slice(&secret.to_bytes())
+        }
+        len => return Err(anyhow!("Invalid sr25519 secret key length: {}", len)),
+    };
+    pair.map_err(|err| anyhow!("Invalid sr25519 secret key: {:?}", err))
+}
+
+/// Checks the public key of `pair` and converts it into the signer.
+fn into_signer<P>(pair: P, public: &[u8]) -> Result<ChainXSigner>
+where
+    P: Pair,
+    ChainXSigner: From<P>,
+{
+    if pair.public().as_ref() != public {
+        return Err(anyhow!("Public key does not match the secret key"));
+    }
+    Ok(pair.into())
+}
+
+/// Returns the secret key in the format of polkadot.js, the public key and the account.
+fn export_pair(scheme: CryptoScheme, uri: &str) -> Result<(Vec<u8>, Vec<u8>, AccountId)> {
+    fn from_uri<P: Pair>(uri: &str) -> Result<P> {
+        P::from_string(uri, None).map_err(|err| anyhow!("Invalid secret URI: {:?}", err))
+    }
+    fn account<P: Into<MultiSigner>>(public: P) -> AccountId {
+        public.into().into_account()
+    }
+
+    Ok(match scheme {
+        CryptoScheme::Sr25519 => {
+            let pair = from_uri::<sr25519::Pair>(uri)?;
+            let secret = schnorrkel::SecretKey::from_bytes(&pair.to_raw_vec())
+                .map_err(|err| anyhow!("Invalid sr25519 secret key: {}", err))?
+                .to_ed25519_bytes()
+                .to_vec();
+            (
+                secret,
+                pair.public().as_ref().to_vec(),
+                account(pair.public()),
+            )
+        }
+        // polkadot.js stores the ed25519 secret key as seed + public key.
+        CryptoScheme::Ed25519 => {
+            let pair = from_uri::<ed25519::Pair>(uri)?;
+            let mut secret = pair.to_raw_vec();
+            secret.extend_from_slice(pair.public().as_ref());
+            (
+                secret,
+                pair.public().as_ref().to_vec(),
+                account(pair.public()),
+            )
+        }
+        CryptoScheme::Ecdsa => {
+            let pair = from_uri::<ecdsa::Pair>(uri)?;
+            (
+                pair.to_raw_vec(),
+                pair.public().as_ref().to_vec(),
+                account(pair.public()),
+            )
+        }
+    })
+}
+
+impl KeyFile {
+    /// Returns the name in meta.
+    pub fn name(&self) -> Option<&str> {
+        self.meta.get("name").and_then(Value::as_str)
+    }
+
+    pub fn set_name(&mut self, name: &str) {
+        self.meta.insert("name".into(), Value::from(name));
+    }
+
+    pub fn account_id(&self) -> Result<AccountId> {
+        AccountId::from_ss58check_with_version(&self.address)
+            .map(|(account, _version)| account)
+            .map_err(|err| anyhow!("Invalid address {}: {:?}", self.address, err))
+    }
+
+    /// Returns the signature scheme of the key.
+    pub fn scheme(&self) -> Result<CryptoScheme> {
+        match self.encoding.content.get(1) {
+            Some(scheme) => scheme
+                .parse()
+                .map_err(|_| anyhow!("Unsupported key scheme: {}", scheme)),
+            None => Ok(CryptoScheme::Sr25519),
+        }
+    }
+
+    /// Encrypts the key pair of `scheme` derived from the secret `uri` with `password`.
+    pub fn encrypt(scheme: CryptoScheme, uri: &str, name: &str, password: &str) -> Result<Self> {
+        let (secret, public, account) = export_pair(scheme, uri)?;
+        let plaintext = encode_pkcs8(&secret, &public);
+
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+
+        let key = scrypt_key(password, &salt, SCRYPT_N, SCRYPT_P, SCRYPT_R)?;
+        let cipher = XSalsa20Poly1305::new(GenericArray::from_slice(&key));
+        let ciphertext = cipher
+            .encrypt(GenericArray::from_slice(&nonce), plaintext.as_slice())
+            .map_err(|_| anyhow!("Failed to encrypt the key"))?;
+
+        let mut encoded = salt.to_vec();
+        for param in &[SCRYPT_N, SCRYPT_P, SCRYPT_R] {
+            encoded.extend_from_slice(&param.to_le_bytes());
+        }
+        encoded.extend_from_slice(&nonce);
+        encoded.extend(ciphertext);
+
+        let mut key_file = Self {
+            encoded: base64::encode(&encoded),
+            encoding: Encoding {
+                content: vec!["pkcs8".into(), scheme.to_string().to_lowercase()],
+                ty: vec!["scrypt".into(), "xsalsa20-poly1305".into()],
+                version: ENCODING_VERSION.into(),
+            },
+            address: account.to_ss58check(),
+            meta: Map::new(),
+        };
+        key_file.set_name(name);
+        Ok(key_file)
+    }
+
+    /// Decrypts the key with `password`.
+    pub fn decrypt(&self, password: &str) -> Result<ChainXSigner> {
+        let scheme = self.scheme()?;
+        let encoded = base64::decode(&self.encoded)?;
+        let plaintext = if self.encoding.ty.iter().any(|ty| ty == "none") {
+            encoded
+        } else if self.encoding.ty.iter().any(|ty| ty == "scrypt") {
+            if encoded.len() < SCRYPT_HEADER_LEN + NONCE_LEN {
+                return Err(anyhow!("Invalid encoded key"));
+            }
+            let (header, data) = encoded.split_at(SCRYPT_HEADER_LEN);
+            let key = scrypt_key(
+                password,
+                &header[..SALT_LEN],
+                read_u32_le(&header[SALT_LEN..]),
+                read_u32_le(&header[SALT_LEN + 4..]),
+                read_u32_le(&header[SALT_LEN + 8..]),
+            )?;
+            let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+            let cipher = XSalsa20Poly1305::new(GenericArray::from_slice(&key));
+            cipher
+                .decrypt(GenericArray::from_slice(nonce), ciphertext)
+                .map_err(|_| anyhow!("Failed to decrypt the key, wrong password?"))?
+        } else {
+            return Err(anyhow!(
+                "Unsupported encryption {:?}, only scrypt is supported",
+                self.encoding.ty
+            ));
+        };
+
+        let (secret, public) = decode_pkcs8(&plaintext)?;
+        let invalid = |err| anyhow!("Invalid {} secret key: {:?}", scheme, err);
+        match scheme {
+            CryptoScheme::Sr25519 => into_signer(sr25519_from_secret(secret)?, public),
+            CryptoScheme::Ed25519 => {
+                let seed = &secret[..SEED_LEN];
+                into_signer(
+                    ed25519::Pair::from_seed_slice(seed).map_err(invalid)?,
+                    public,
+                )
+            }
+            CryptoScheme::Ecdsa => into_signer(
+                ecdsa::Pair::from_seed_slice(secret).map_err(invalid)?,
+                public,
+            ),
+        }
+    }
+}
+
+/// The directory storing the encrypted accounts.
+pub struct Keystore {
+    path: PathBuf,
+}
+
+impl Keystore {
+    /// Returns `~/.chainx-cli/keystore`.
+    pub fn default_path() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_default()
+            .join(".chainx-cli")
+            .join("keystore")
+    }
+
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        fs::create_dir_all(&path)?;
+        Ok(Self { path })
+    }
+
+    fn file_path(&self, name: &str) -> Result<PathBuf> {
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(anyhow!(
+                "Invalid account name {:?}, only letters, digits, `-` and `_` are allowed",
+                name
+            ));
+        }
+        Ok(self.path.join(format!("{}.json", name)))
+    }
+
+    /// Stores `key_file` under `name`, an existing account won't be overwritten.
+    pub fn insert(&self, name: &str, key_file: &KeyFile) -> Result<()> {
+        let path = self.file_path(name)?;
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let file = options.open(&path).map_err(|err| match err.kind() {
+            std::io::ErrorKind::AlreadyExists => anyhow!("Account {} already exists", name),
+            _ => anyhow!("Failed to create {}: {}", path.display(), err),
+        })?;
+        Ok(serde_json::to_writer_pretty(file, key_file)?)
+    }
+
+    pub fn get(&self, name: &str) -> Result<KeyFile> {
+        let path = self.file_path(name)?;
+        let file = File::open(&path).map_err(|_| anyhow!("Account {} not found", name))?;
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    pub fn remove(&self, name: &str) -> Result<()> {
+        let path = self.file_path(name)?;
+        fs::remove_file(&path).map_err(|_| anyhow!("Account {} not found", name))
+    }
+
+    /// Returns all the accounts sorted by name.
+    pub fn list(&self) -> Result<Vec<(String, KeyFile)>> {
+        let mut accounts = Vec::new();
+        for entry in fs::read_dir(&self.path)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            if let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) {
+                accounts.push((name.to_string(), self.get(name)?));
+            }
+        }
+        accounts.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(accounts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pkcs8_roundtrip() {
+        let secret = [1u8; SECRET_KEY_LEN];
+        let public = [2u8; PUBLIC_KEY_LEN];
+        let data = encode_pkcs8(&secret, &public);
+        assert_eq!(decode_pkcs8(&data).unwrap(), (&secret[..], &public[..]));
+
+        let seed = [3u8; SEED_LEN];
+        let data = encode_pkcs8(&seed, &public);
+        assert_eq!(decode_pkcs8(&data).unwrap(), (&seed[..], &public[..]));
+    }
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        use subxt::Signer;
+
+        for scheme in &[
+            CryptoScheme::Sr25519,
+            CryptoScheme::Ed25519,
+            CryptoScheme::Ecdsa,
+        ] {
+            let uri = "//Alice//stash///password";
+            let expected = ChainXSigner::from_uri(*scheme, uri).unwrap();
+            let key_file = KeyFile::encrypt(*scheme, uri, "test", "password").unwrap();
+            assert_eq!(key_file.name(), Some("test"));
+            assert_eq!(key_file.scheme().unwrap(), *scheme);
+            assert_eq!(&key_file.account_id().unwrap(), expected.account_id());
+
+            let json = serde_json::to_string(&key_file).unwrap();
+            let key_file: KeyFile = serde_json::from_str(&json).unwrap();
+            let decrypted = key_file.decrypt("password").unwrap();
+            assert_eq!(decrypted.account_id(), expected.account_id());
+            assert!(key_file.decrypt("wrong").is_err());
+        }
+    }
+}
@@ -1,8 +1,11 @@
 mod app;
 mod frame;
+mod keystore;
+mod metadata;
 pub mod rpc;
 pub mod runtime;
 mod serde;
+pub mod units;
 mod utils;
 
 pub use self::app::App;
@@ -0,0 +1,357 @@
+//! Compatibility of the pallets hard-coded in `ChainXRuntime` with the runtime metadata.
+//!
+//! The calls and storages under `runtime::xpallets` and `frame::session` are encoded and
+//! decoded with fixed layouts, which silently mis-decode after a runtime upgrade changes
+//! them. Each of those modules declares the layouts it relies on, which are compared with
+//! the metadata of the node here.
+//!
+//! Only the type names are available in the metadata, so the fields inside a struct like
+//! `ValidatorProfile` are checked by its name only.
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use structopt::clap::arg_enum;
+
+use super::{same_type, Metadata, ModuleMetadata};
+use crate::rpc::{storage::StorageHasher, Rpc};
+
+/// The calls and storages of a pallet that are hard-coded.
+#[derive(Debug)]
+pub struct PalletDecl {
+    pub name: &'static str,
+    pub calls: &'static [CallDecl],
+    pub storage: &'static [StorageDecl],
+}
+
+/// A call and its `(name, type)` arguments in order.
+#[derive(Debug)]
+pub struct CallDecl {
+    pub name: &'static str,
+    pub args: &'static [(&'static str, &'static str)],
+}
+
+/// A storage entry, the keys are `(hasher, type)`.
+///
+/// The hasher is `None` if it's read from the metadata, e.g., by the `Store` of subxt.
+#[derive(Debug)]
+pub struct StorageDecl {
+    pub name: &'static str,
+    pub keys: &'static [(Option<StorageHasher>, &'static str)],
+    pub value: &'static str,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Not affecting the encoding, e.g., a renamed argument.
+    Warning,
+    /// The call or storage will be mis-encoded or mis-decoded.
+    Error,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Issue {
+    pub severity: Severity,
+    /// e.g., `XStaking::bond` or `XStaking::Validators`.
+    pub item: String,
+    pub message: String,
+}
+
+/// The result of comparing the declared pallets with the metadata.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompatReport {
+    /// Number of the declared calls and storages checked.
+    pub checked: usize,
+    pub issues: Vec<Issue>,
+}
+
+impl CompatReport {
+    fn push(&mut self, severity: Severity, item: String, message: String) {
+        self.issues.push(Issue {
+            severity,
+            item,
+            message,
+        });
+    }
+
+    /// Returns the number of issues of `severity`.
+    pub fn count(&self, severity: Severity) -> usize {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == severity)
+            .count()
+    }
+
+    pub fn print_text(&self) {
+        for issue in &self.issues {
+            let severity = match issue.severity {
+                Severity::Warning => "warning",
+                Severity::Error => "error",
+            };
+            println!("{}: {}: {}", severity, issue.item, issue.message);
+        }
+        println!(
+            "Checked {} calls and storages: {} errors, {} warnings",
+            self.checked,
+            self.count(Severity::Error),
+            self.count(Severity::Warning)
+        );
+    }
+}
+
+fn check_call(report: &mut CompatReport, module: &ModuleMetadata, decl: &CallDecl) {
+    let item = format!("{}::{}", module.name, decl.name);
+    let call = match module.call(decl.name) {
+        Ok((_, call)) => call,
+        Err(_) => return report.push(Severity::Error, item, "not found".into()),
+    };
+    if call.args.len() != decl.args.len() {
+        let message = format!(
+            "{} arguments declared, {} in metadata",
+            decl.args.len(),
+            call.args.len()
+        );
+        return report.push(Severity::Error, item, message);
+    }
+    for (index, ((name, ty), arg)) in decl.args.iter().zip(&call.args).enumerate() {
+        if !same_type(ty, &arg.ty) {
+            let message = format!(
+                "argument #{} `{}` is declared as `{}`, `{}` in metadata",
+                index, name, ty, arg.ty
+            );
+            report.push(Severity::Error, item.clone(), message);
+        } else if *name != arg.name {
+            let message = format!(
+                "argument #{} is declared as `{}`, `{}` in metadata",
+                index, name, arg.name
+            );
+            report.push(Severity::Warning, item.clone(), message);
+        }
+    }
+}
+
+fn check_storage(report: &mut CompatReport, module: &ModuleMetadata, decl: &StorageDecl) {
+    let item = format!("{}::{}", module.name, decl.name);
+    let storage = match module.storage(decl.name) {
+        Ok(storage) => storage,
+        Err(_) => return report.push(Severity::Error, item, "not found".into()),
+    };
+    let keys = storage.keys();
+    if keys.len() != decl.keys.len() {
+        let message = format!(
+            "{} keys declared, {} in metadata",
+            decl.keys.len(),
+            keys.len()
+        );
+        return report.push(Severity::Error, item, message);
+    }
+    for (index, ((hasher, ty), (actual_hasher, actual_ty))) in
+        decl.keys.iter().zip(keys).enumerate()
+    {
+        if !same_type(ty, actual_ty) {
+            let message = format!(
+                "key #{} is declared as `{}`, `{}` in metadata",
+                index, ty, actual_ty
+            );
+            report.push(Severity::Error, item.clone(), message);
+        }
+        match hasher {
+            Some(hasher) if *hasher != actual_hasher => {
+                let message = format!(
+                    "hasher of key #{} is declared as {:?}, {:?} in metadata",
+                    index, hasher, actual_hasher
+                );
+                report.push(Severity::Error, item.clone(), message);
+            }
+            _ => {}
+        }
+    }
+    if !same_type(decl.value, storage.value_ty()) {
+        let message = format!(
+            "value is declared as `{}`, `{}` in metadata",
+            decl.value,
+            storage.value_ty()
+        );
+        report.push(Severity::Error, item, message);
+    }
+}
+
+/// Compares the declared `pallets` with `metadata`.
+pub fn check_compat(metadata: &Metadata, pallets: &[PalletDecl]) -> CompatReport {
+    let mut report = CompatReport::default();
+    for pallet in pallets {
+        let module = match metadata.module(pallet.name) {
+            Ok(module) => module,
+            Err(_) => {
+                report.push(Severity::Error, pallet.name.into(), "not found".into());
+                continue;
+            }
+        };
+        for call in pallet.calls {
+            check_call(&mut report, module, call);
+        }
+        for storage in pallet.storage {
+            check_storage(&mut report, module, storage);
+        }
+        report.checked += pallet.calls.len() + pallet.storage.len();
+    }
+    report
+}
+
+arg_enum! {
+  /// What to do when the runtime metadata is incompatible at startup.
+  #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+  pub enum CompatCheck {
+      Off,
+      Warn,
+      Abort,
+  }
+}
+
+/// Checks the declared pallets against the metadata of the node connected by `rpc`.
+///
+/// The errors are printed to stderr, or returned with `CompatCheck::Abort`. A failure of
+/// fetching the metadata is only a warning unless it's `CompatCheck::Abort`.
+pub async fn check_at_startup(rpc: &Rpc, check: CompatCheck) -> Result<()> {
+    if check == CompatCheck::Off {
+        return Ok(());
+    }
+
+    let metadata = match rpc.metadata(None).await {
+        Ok(metadata) => metadata,
+        Err(err) if check == CompatCheck::Warn => {
+            eprintln!(
+                "Warning: failed to fetch the runtime metadata for the compatibility check: {}",
+                err
+            );
+            return Ok(());
+        }
+        Err(err) => return Err(err),
+    };
+    let report = check_compat(&metadata, crate::runtime::PALLETS);
+    let errors = report.count(Severity::Error);
+    if errors == 0 {
+        return Ok(());
+    }
+    if check == CompatCheck::Abort {
+        return Err(anyhow!(
+            "The runtime metadata is incompatible with chainx-cli in {} places, \
+             see `chainx-cli check-compat` for the report",
+            errors
+        ));
+    }
+    for issue in report
+        .issues
+        .iter()
+        .filter(|issue| issue.severity == Severity::Error)
+    {
+        eprintln!(
+            "Warning: incompatible runtime metadata: {}: {}",
+            issue.item, issue.message
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::metadata::{CallArgMetadata, CallMetadata, StorageMetadata, StorageTypeMetadata};
+
+    const PALLETS: &[PalletDecl] = &[PalletDecl {
+        name: "XStaking",
+        calls: &[
+            CallDecl {
+                name: "bond",
+                args: &[
+                    ("target", "<T::Lookup as StaticLookup>::Source"),
+                    ("value", "Compact<BalanceOf<T>>"),
+                ],
+            },
+            CallDecl {
+                name: "chill",
+                args: &[],
+            },
+        ],
+        storage: &[StorageDecl {
+            name: "Validators",
+            keys: &[(Some(StorageHasher::Twox64Concat), "T::AccountId")],
+            value: "ValidatorProfile<T::BlockNumber>",
+        }],
+    }];
+
+    fn module(arg: &str, arg_ty: &str, hasher: StorageHasher) -> ModuleMetadata {
+        ModuleMetadata {
+            name: "XStaking".into(),
+            index: 1,
+            storage_prefix: "XStaking".into(),
+            storage: vec![StorageMetadata {
+                name: "Validators".into(),
+                ty: StorageTypeMetadata::Map {
+                    hasher,
+                    key: "T::AccountId".into(),
+                    value: "ValidatorProfile<T::BlockNumber>".into(),
+                },
+                is_optional: false,
+                default: Vec::new(),
+                documentation: Vec::new(),
+            }],
+            calls: vec![CallMetadata {
+                name: "bond".into(),
+                args: vec![
+                    CallArgMetadata {
+                        name: "target".into(),
+                        ty: "<T::Lookup as StaticLookup>::Source".into(),
+                    },
+                    CallArgMetadata {
+                        name: arg.into(),
+                        ty: arg_ty.into(),
+                    },
+                ],
+                documentation: Vec::new(),
+            }],
+            constants: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_check_compat() {
+        let metadata = Metadata {
+            modules: vec![module(
+                "value",
+                "Compact<BalanceOf<T>>",
+                StorageHasher::Twox64Concat,
+            )],
+        };
+        let report = check_compat(&metadata, PALLETS);
+        assert_eq!(report.checked, 3);
+        // `chill` is missing.
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].item, "XStaking::chill");
+
+        let metadata = Metadata {
+            modules: vec![module(
+                "amount",
+                "BalanceOf<T>",
+                StorageHasher::Blake2_128Concat,
+            )],
+        };
+        let report = check_compat(&metadata, PALLETS);
+        assert_eq!(report.count(Severity::Error), 3);
+        assert_eq!(report.count(Severity::Warning), 0);
+
+        let metadata = Metadata {
+            modules: vec![module(
+                "amount",
+                "Compact<BalanceOf<T>>",
+                StorageHasher::Twox64Concat,
+            )],
+        };
+        let report = check_compat(&metadata, PALLETS);
+        assert_eq!(report.count(Severity::Error), 1);
+        assert_eq!(report.count(Severity::Warning), 1);
+    }
+}
@@ -0,0 +1,470 @@
+//! Runtime metadata of the connected node.
+//!
+//! Unlike the `Metadata` of subxt, the argument types are retained so that the calls
+//! can be encoded and decoded dynamically.
+
+pub mod compat;
+mod types;
+
+use anyhow::{anyhow, Result};
+use codec::Decode;
+use frame_metadata::{
+    DecodeDifferent, RuntimeMetadata, RuntimeMetadataPrefixed, StorageEntryModifier,
+    StorageEntryType,
+};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::rpc::{storage::StorageHasher, storage_prefix_for};
+
+pub use self::types::{decode_value, encode_str, encode_value, same_type};
+
+/// Unwraps the decoded variant of `DecodeDifferent`.
+fn decoded<B, O>(value: DecodeDifferent<B, O>) -> Result<O>
+where
+    B: 'static,
+    O: 'static,
+{
+    match value {
+        DecodeDifferent::Decoded(value) => Ok(value),
+        DecodeDifferent::Encode(_) => Err(anyhow!("Metadata is not decoded")),
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CallArgMetadata {
+    pub name: String,
+    pub ty: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct CallMetadata {
+    pub name: String,
+    pub args: Vec<CallArgMetadata>,
+    pub documentation: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ConstantMetadata {
+    pub name: String,
+    pub ty: String,
+    /// SCALE encoded value.
+    pub value: Vec<u8>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ErrorMetadata {
+    pub name: String,
+    pub documentation: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+pub enum StorageTypeMetadata {
+    Plain {
+        value: String,
+    },
+    Map {
+        hasher: StorageHasher,
+        key: String,
+        value: String,
+    },
+    DoubleMap {
+        hasher1: StorageHasher,
+        key1: String,
+        hasher2: StorageHasher,
+        key2: String,
+        value: String,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct StorageMetadata {
+    pub name: String,
+    pub ty: StorageTypeMetadata,
+    /// Whether the value is `Option`, i.e., no value if the key does not exist.
+    pub is_optional: bool,
+    /// SCALE encoded value if the key does not exist.
+    pub default: Vec<u8>,
+    pub documentation: Vec<String>,
+}
+
+impl StorageMetadata {
+    /// Returns the hashers and the types of the map keys, empty for the plain storage.
+    pub fn keys(&self) -> Vec<(StorageHasher, &str)> {
+        match &self.ty {
+            StorageTypeMetadata::Plain { .. } => Vec::new(),
+            StorageTypeMetadata::Map { hasher, key, .. } => vec![(*hasher, key)],
+            StorageTypeMetadata::DoubleMap {
+                hasher1,
+                key1,
+                hasher2,
+                key2,
+                ..
+            } => vec![(*hasher1, key1), (*hasher2, key2)],
+        }
+    }
+
+    pub fn value_ty(&self) -> &str {
+        match &self.ty {
+            StorageTypeMetadata::Plain { value }
+            | StorageTypeMetadata::Map { value, .. }
+            | StorageTypeMetadata::DoubleMap { value, .. } => value,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ModuleMetadata {
+    pub name: String,
+    pub index: u8,
+    /// Prefix of the storage keys, usually the same as the module name.
+    pub storage_prefix: String,
+    pub storage: Vec<StorageMetadata>,
+    pub calls: Vec<CallMetadata>,
+    pub constants: Vec<ConstantMetadata>,
+    pub errors: Vec<ErrorMetadata>,
+}
+
+impl ModuleMetadata {
+    /// Returns the call index and the call metadata of `name`.
+    ///
+    /// Both `set_keys` and `set-keys` are accepted.
+    pub fn call(&self, name: &str) -> Result<(u8, &CallMetadata)> {
+        let name = name.replace('-', "_");
+        self.calls
+            .iter()
+            .enumerate()
+            .find(|(_, call)| call.name == name)
+            .map(|(index, call)| (index as u8, call))
+            .ok_or_else(|| anyhow!("Call {}::{} not found in metadata", self.name, name))
+    }
+
+    /// Returns the storage entry `name`, case insensitive.
+    pub fn storage(&self, name: &str) -> Result<&StorageMetadata> {
+        self.storage
+            .iter()
+            .find(|storage| storage.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| anyhow!("Storage {}::{} not found in metadata", self.name, name))
+    }
+}
+
+/// The decoded call for displaying.
+#[derive(Clone, Debug, Serialize)]
+pub struct DecodedCall {
+    pub module: String,
+    pub call: String,
+    pub args: Map<String, Value>,
+}
+
+/// Max length of the string argument displayed in full.
+const MAX_DISPLAY_LEN: usize = 130;
+
+fn abbreviate(value: &mut Value) {
+    match value {
+        Value::String(s) if s.chars().count() > MAX_DISPLAY_LEN => {
+            let head = s.chars().take(MAX_DISPLAY_LEN).collect::<String>();
+            *s = format!("{}...({} chars)", head, s.chars().count());
+        }
+        Value::Array(values) => values.iter_mut().for_each(abbreviate),
+        Value::Object(map) => map.values_mut().for_each(abbreviate),
+        _ => {}
+    }
+}
+
+impl DecodedCall {
+    /// Truncates the long arguments, e.g., the runtime code of `set_code`.
+    pub fn abbreviated(mut self) -> Self {
+        self.args.values_mut().for_each(abbreviate);
+        self
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Metadata {
+    pub modules: Vec<ModuleMetadata>,
+}
+
+impl Metadata {
+    /// Decodes the SCALE encoded metadata returned by RPC `state_getMetadata`.
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let prefixed = RuntimeMetadataPrefixed::decode(&mut &bytes[..])?;
+        let metadata = match prefixed.1 {
+            RuntimeMetadata::V12(metadata) => metadata,
+            _ => return Err(anyhow!("Only metadata V12 is supported")),
+        };
+
+        let mut modules = Vec::new();
+        for module in decoded(metadata.modules)? {
+            let mut storage_prefix = String::new();
+            let mut storage = Vec::new();
+            if let Some(module_storage) = module.storage.map(decoded).transpose()? {
+                storage_prefix = decoded(module_storage.prefix)?;
+                for entry in decoded(module_storage.entries)? {
+                    let ty = match entry.ty {
+                        StorageEntryType::Plain(value) => StorageTypeMetadata::Plain {
+                            value: decoded(value)?,
+                        },
+                        StorageEntryType::Map {
+                            hasher, key, value, ..
+                        } => StorageTypeMetadata::Map {
+                            hasher: hasher.into(),
+                            key: decoded(key)?,
+                            value: decoded(value)?,
+                        },
+                        StorageEntryType::DoubleMap {
+                            hasher,
+                            key1,
+                            key2,
+                            value,
+                            key2_hasher,
+                        } => StorageTypeMetadata::DoubleMap {
+                            hasher1: hasher.into(),
+                            key1: decoded(key1)?,
+                            hasher2: key2_hasher.into(),
+                            key2: decoded(key2)?,
+                            value: decoded(value)?,
+                        },
+                    };
+                    storage.push(StorageMetadata {
+                        name: decoded(entry.name)?,
+                        ty,
+                        is_optional: matches!(entry.modifier, StorageEntryModifier::Optional),
+                        default: decoded(entry.default)?,
+                        documentation: decoded(entry.documentation)?,
+                    });
+                }
+            }
+
+            let mut calls = Vec::new();
+            for call in module.calls.map(decoded).transpose()?.unwrap_or_default() {
+                let mut args = Vec::new();
+                for arg in decoded(call.arguments)? {
+                    args.push(CallArgMetadata {
+                        name: decoded(arg.name)?,
+                        ty: decoded(arg.ty)?,
+                    });
+                }
+                calls.push(CallMetadata {
+                    name: decoded(call.name)?,
+                    args,
+                    documentation: decoded(call.documentation)?,
+                });
+            }
+
+            let mut constants = Vec::new();
+            for constant in decoded(module.constants)? {
+                constants.push(ConstantMetadata {
+                    name: decoded(constant.name)?,
+                    ty: decoded(constant.ty)?,
+                    value: decoded(constant.value)?,
+                });
+            }
+
+            let mut errors = Vec::new();
+            for error in decoded(module.errors)? {
+                errors.push(ErrorMetadata {
+                    name: decoded(error.name)?,
+                    documentation: decoded(error.documentation)?,
+                });
+            }
+
+            modules.push(ModuleMetadata {
+                name: decoded(module.name)?,
+                index: module.index,
+                storage_prefix,
+                storage,
+                calls,
+                constants,
+                errors,
+            });
+        }
+
+        Ok(Self { modules })
+    }
+
+    pub fn module(&self, name: &str) -> Result<&ModuleMetadata> {
+        self.modules
+            .iter()
+            .find(|module| module.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| anyhow!("Module {} not found in metadata", name))
+    }
+
+    pub fn module_by_index(&self, index: u8) -> Result<&ModuleMetadata> {
+        self.modules
+            .iter()
+            .find(|module| module.index == index)
+            .ok_or_else(|| anyhow!("Module index {} not found in metadata", index))
+    }
+
+    /// Returns the error `error` of the module with index `index`, i.e., `DispatchError::Module`.
+    pub fn module_error(&self, index: u8, error: u8) -> Result<(&ModuleMetadata, &ErrorMetadata)> {
+        let module = self.module_by_index(index)?;
+        let error = module
+            .errors
+            .get(error as usize)
+            .ok_or_else(|| anyhow!("Error index {} not found in module {}", error, module.name))?;
+        Ok((module, error))
+    }
+
+    /// Returns the error `module::name`.
+    pub fn error_by_name(
+        &self,
+        module: &str,
+        name: &str,
+    ) -> Result<(&ModuleMetadata, &ErrorMetadata)> {
+        let module = self.module(module)?;
+        let error = module
+            .errors
+            .iter()
+            .find(|error| error.name == name)
+            .ok_or_else(|| anyhow!("Error {}::{} not found in metadata", module.name, name))?;
+        Ok((module, error))
+    }
+
+    /// Returns the decoded value of constant `module::name`.
+    pub fn constant<V: Decode>(&self, module: &str, name: &str) -> Result<V> {
+        let module = self.module(module)?;
+        let constant = module
+            .constants
+            .iter()
+            .find(|constant| constant.name == name)
+            .ok_or_else(|| anyhow!("Constant {}::{} not found in metadata", module.name, name))?;
+        Ok(V::decode(&mut constant.value.as_slice())?)
+    }
+
+    /// Encodes the call `module::call` with the arguments given in string.
+    pub fn encode_call(&self, module: &str, call: &str, args: &[String]) -> Result<Vec<u8>> {
+        let module = self.module(module)?;
+        let (call_index, call) = module.call(call)?;
+        if call.args.len() != args.len() {
+            return Err(anyhow!(
+                "{}::{} expects {} arguments ({}), got {}",
+                module.name,
+                call.name,
+                call.args.len(),
+                call.args
+                    .iter()
+                    .map(|arg| format!("{}: {}", arg.name, arg.ty))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                args.len()
+            ));
+        }
+
+        let mut encoded = vec![module.index, call_index];
+        for (arg, value) in call.args.iter().zip(args) {
+            encoded.extend(
+                encode_str(self, &arg.ty, value)
+                    .map_err(|err| anyhow!("Invalid argument {}: {}", arg.name, err))?,
+            );
+        }
+        Ok(encoded)
+    }
+
+    /// Decodes the SCALE encoded call.
+    pub fn decode_call(&self, input: &mut &[u8]) -> Result<DecodedCall> {
+        let module_index = u8::decode(input)?;
+        let call_index = u8::decode(input)?;
+        let module = self.module_by_index(module_index)?;
+        let call = module.calls.get(call_index as usize).ok_or_else(|| {
+            anyhow!(
+                "Call index {} not found in module {}",
+                call_index,
+                module.name
+            )
+        })?;
+
+        let mut args = Map::new();
+        for arg in &call.args {
+            args.insert(arg.name.clone(), decode_value(self, &arg.ty, input)?);
+        }
+
+        Ok(DecodedCall {
+            module: module.name.clone(),
+            call: call.name.clone(),
+            args,
+        })
+    }
+
+    /// Returns the storage key of `module::name` with the map keys given in string.
+    ///
+    /// The trailing keys can be omitted, then the returned key is the prefix of the entries.
+    pub fn encode_storage_key(&self, module: &str, name: &str, keys: &[String]) -> Result<Vec<u8>> {
+        let module = self.module(module)?;
+        let storage = module.storage(name)?;
+        let key_types = storage.keys();
+        if keys.len() > key_types.len() {
+            return Err(anyhow!(
+                "{}::{} expects at most {} keys ({}), got {}",
+                module.name,
+                storage.name,
+                key_types.len(),
+                key_types
+                    .iter()
+                    .map(|(_, ty)| *ty)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                keys.len()
+            ));
+        }
+
+        let mut encoded = storage_prefix_for(&module.storage_prefix, &storage.name);
+        for ((hasher, ty), key) in key_types.into_iter().zip(keys) {
+            let key =
+                encode_str(self, ty, key).map_err(|err| anyhow!("Invalid key {}: {}", ty, err))?;
+            encoded.extend(hasher.hash(&key));
+        }
+        Ok(encoded)
+    }
+
+    /// Decodes the map keys of `module::name` from the full storage key.
+    ///
+    /// The keys hashed by the non-concat hashers can't be decoded, the hashes are returned instead.
+    pub fn decode_storage_key(&self, module: &str, name: &str, key: &[u8]) -> Result<Vec<Value>> {
+        let module = self.module(module)?;
+        let storage = module.storage(name)?;
+        let prefix = storage_prefix_for(&module.storage_prefix, &storage.name);
+        let mut input = key
+            .strip_prefix(prefix.as_slice())
+            .ok_or_else(|| anyhow!("Not a storage key of {}::{}", module.name, storage.name))?;
+
+        let mut keys = Vec::new();
+        for (hasher, ty) in storage.keys() {
+            if hasher.is_concat() {
+                hasher.skip_hash(&mut input)?;
+                keys.push(decode_value(self, ty, &mut input)?);
+            } else {
+                if input.len() < hasher.hash_len() {
+                    return Err(anyhow!("Storage key is shorter than the {:?} hash", hasher));
+                }
+                let (hash, rest) = input.split_at(hasher.hash_len());
+                keys.push(Value::from(format!("0x{}", hex::encode(hash))));
+                input = rest;
+            }
+        }
+        if !input.is_empty() {
+            return Err(anyhow!(
+                "{} trailing bytes after decoding the storage key",
+                input.len()
+            ));
+        }
+        Ok(keys)
+    }
+
+    /// Decodes the storage value of `module::name`, `value` is `None` if the key does not exist.
+    ///
+    /// The default value is returned for the non-optional storage if the key does not exist.
+    pub fn decode_storage_value(
+        &self,
+        module: &str,
+        name: &str,
+        value: Option<&[u8]>,
+    ) -> Result<Value> {
+        let storage = self.module(module)?.storage(name)?;
+        match value {
+            Some(mut value) => decode_value(self, storage.value_ty(), &mut value),
+            None if storage.is_optional => Ok(Value::Null),
+            None => decode_value(self, storage.value_ty(), &mut storage.default.as_slice()),
+        }
+    }
+}
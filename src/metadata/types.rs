@@ -0,0 +1,702 @@
+//! Dynamic SCALE encoding and decoding driven by the type names in metadata.
+//!
+//! Only the types used by the ChainX runtime calls and storages are supported,
+//! an error is returned for the others.
+
+use std::{any::type_name, convert::TryFrom};
+
+use anyhow::{anyhow, Result};
+use codec::{Compact, Decode, Encode};
+use serde_json::{Map, Value};
+use sp_core::crypto::Ss58Codec;
+use sp_runtime::traits::OpaqueKeys;
+
+use super::Metadata;
+use crate::{
+    runtime::{
+        primitives::{AccountId, Address},
+        BasicSessionKeys, SESSION_KEY_LEN,
+    },
+    utils::parse_account,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Ty {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    Compact(Box<Ty>),
+    /// `Vec<u8>` displayed in hex.
+    Bytes,
+    /// `Vec<u8>` displayed in utf8.
+    Text,
+    /// `[u8; N]` displayed in hex.
+    FixedBytes(usize),
+    AccountId,
+    LookupSource,
+    Call,
+    Vec(Box<Ty>),
+    Option(Box<Ty>),
+    Tuple(Vec<Ty>),
+    Map(Box<Ty>, Box<Ty>),
+    Enum(&'static [&'static str]),
+    /// Struct of the field names and type names.
+    Struct(&'static [(&'static str, &'static str)]),
+}
+
+const ASSET_TYPE_VARIANTS: &[&str] = &[
+    "Usable",
+    "Locked",
+    "Reserved",
+    "ReservedWithdrawal",
+    "ReservedDexSpot",
+];
+
+const LOCKED_TYPE_VARIANTS: &[&str] = &["Bonded", "BondedWithdrawal"];
+
+const ACCOUNT_INFO_FIELDS: &[(&str, &str)] = &[
+    ("nonce", "Index"),
+    ("refcount", "RefCount"),
+    ("data", "AccountData"),
+];
+
+const ACCOUNT_DATA_FIELDS: &[(&str, &str)] = &[
+    ("free", "Balance"),
+    ("reserved", "Balance"),
+    ("misc_frozen", "Balance"),
+    ("fee_frozen", "Balance"),
+];
+
+const VALIDATOR_PROFILE_FIELDS: &[(&str, &str)] = &[
+    ("registered_at", "BlockNumber"),
+    ("is_chilled", "bool"),
+    ("last_chilled", "Option<BlockNumber>"),
+    ("referral_id", "ReferralId"),
+];
+
+const VALIDATOR_LEDGER_FIELDS: &[(&str, &str)] = &[
+    ("total_nomination", "Balance"),
+    ("last_total_vote_weight", "VoteWeight"),
+    ("last_total_vote_weight_update", "BlockNumber"),
+];
+
+const NOMINATOR_LEDGER_FIELDS: &[(&str, &str)] = &[
+    ("nomination", "Balance"),
+    ("last_vote_weight", "VoteWeight"),
+    ("last_vote_weight_update", "BlockNumber"),
+    ("unbonded_chunks", "Vec<Unbonded>"),
+];
+
+const UNBONDED_FIELDS: &[(&str, &str)] = &[("value", "Balance"), ("locked_until", "BlockNumber")];
+
+const ASSET_LEDGER_FIELDS: &[(&str, &str)] = &[
+    ("last_total_mining_weight", "MiningWeight"),
+    ("last_total_mining_weight_update", "BlockNumber"),
+];
+
+const MINER_LEDGER_FIELDS: &[(&str, &str)] = &[
+    ("last_mining_weight", "MiningWeight"),
+    ("last_mining_weight_update", "BlockNumber"),
+    ("last_claim", "Option<BlockNumber>"),
+];
+
+/// Splits `s` by `sep` at the top level, i.e., not inside any brackets.
+fn split_top_level(s: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth -= 1,
+            c if c == sep && depth == 0 => {
+                parts.push(s[start..i].trim());
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(s[start..].trim());
+    parts.into_iter().filter(|part| !part.is_empty()).collect()
+}
+
+/// Strips the path qualifiers of a type name, e.g., `<T as Trait>::Call` => `Call`,
+/// `T::AccountId` => `AccountId`.
+fn strip_path(ty: &str) -> &str {
+    let ty = ty.trim();
+    if ty.starts_with('<') {
+        // Find the `>` closing the leading `<`.
+        let mut depth = 0i32;
+        for (i, c) in ty.char_indices() {
+            match c {
+                '<' => depth += 1,
+                '>' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return match ty[i + 1..].strip_prefix("::") {
+                            Some(rest) => strip_path(rest),
+                            None => ty,
+                        };
+                    }
+                }
+                _ => {}
+            }
+        }
+        return ty;
+    }
+
+    let generic_start = ty.find('<').unwrap_or_else(|| ty.len());
+    match ty[..generic_start].rfind("::") {
+        Some(pos) => &ty[pos + 2..],
+        None => ty,
+    }
+}
+
+fn parse_type(ty: &str) -> Result<Ty> {
+    let ty = strip_path(ty);
+
+    if let Some(inner) = ty.strip_prefix('(').and_then(|s| s.strip_suffix(')')) {
+        let types = split_top_level(inner, ',')
+            .into_iter()
+            .map(parse_type)
+            .collect::<Result<Vec<_>>>()?;
+        return Ok(Ty::Tuple(types));
+    }
+
+    if let Some(inner) = ty.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+        let parts = split_top_level(inner, ';');
+        if parts.len() == 2 && parse_type(parts[0])? == Ty::U8 {
+            return Ok(Ty::FixedBytes(parts[1].parse()?));
+        }
+        return Err(anyhow!("Unsupported type: {}", ty));
+    }
+
+    let (name, params) = match ty.find('<') {
+        Some(pos) if ty.ends_with('>') => {
+            (&ty[..pos], split_top_level(&ty[pos + 1..ty.len() - 1], ','))
+        }
+        _ => (ty, Vec::new()),
+    };
+
+    let param = |index: usize| -> Result<Ty> {
+        params
+            .get(index)
+            .ok_or_else(|| anyhow!("Missing type parameter of {}", ty))
+            .and_then(|param| parse_type(param))
+    };
+
+    let ty = match name {
+        "bool" => Ty::Bool,
+        "u8" | "Percent" => Ty::U8,
+        "u16" => Ty::U16,
+        "u32" | "BlockNumber" | "Index" | "AssetId" | "SessionIndex" | "EraIndex"
+        | "AccountIndex" | "MemberCount" | "RefCount" | "AuthorityIndex" | "Perbill"
+        | "Permill" => Ty::U32,
+        "u64" | "Weight" | "Moment" => Ty::U64,
+        "u128" | "Balance" | "BalanceOf" | "VoteWeight" | "MiningWeight" => Ty::U128,
+        "AccountId" | "ValidatorId" => Ty::AccountId,
+        "Source" | "LookupSource" | "Address" => Ty::LookupSource,
+        "Hash" | "H256" => Ty::FixedBytes(32),
+        "Keys" => Ty::FixedBytes(BasicSessionKeys::key_ids().len() * SESSION_KEY_LEN),
+        "Call" => Ty::Call,
+        "Bytes" => Ty::Bytes,
+        "ReferralId" | "Text" => Ty::Text,
+        "AssetType" => Ty::Enum(ASSET_TYPE_VARIANTS),
+        "LockedType" => Ty::Enum(LOCKED_TYPE_VARIANTS),
+        // The generic parameters of the structs are the same in the ChainX runtime,
+        // so the field types are fixed.
+        "AccountInfo" => Ty::Struct(ACCOUNT_INFO_FIELDS),
+        "AccountData" => Ty::Struct(ACCOUNT_DATA_FIELDS),
+        "ValidatorProfile" => Ty::Struct(VALIDATOR_PROFILE_FIELDS),
+        "ValidatorLedger" => Ty::Struct(VALIDATOR_LEDGER_FIELDS),
+        "NominatorLedger" => Ty::Struct(NOMINATOR_LEDGER_FIELDS),
+        "Unbonded" => Ty::Struct(UNBONDED_FIELDS),
+        "AssetLedger" => Ty::Struct(ASSET_LEDGER_FIELDS),
+        "MinerLedger" => Ty::Struct(MINER_LEDGER_FIELDS),
+        "Box" => param(0)?,
+        "Compact" => Ty::Compact(Box::new(param(0)?)),
+        "Option" => Ty::Option(Box::new(param(0)?)),
+        "Vec" => match param(0)? {
+            Ty::U8 => Ty::Bytes,
+            inner => Ty::Vec(Box::new(inner)),
+        },
+        "BTreeMap" => Ty::Map(Box::new(param(0)?), Box::new(param(1)?)),
+        _ => return Err(anyhow!("Unsupported type: {}", ty)),
+    };
+
+    Ok(ty)
+}
+
+fn as_u128(value: &Value) -> Result<u128> {
+    match value {
+        Value::Number(n) => n
+            .as_u64()
+            .map(u128::from)
+            .ok_or_else(|| anyhow!("Expected unsigned integer, got {}", n)),
+        Value::String(s) => Ok(s.replace('_', "").parse()?),
+        _ => Err(anyhow!("Expected unsigned integer, got {}", value)),
+    }
+}
+
+/// Returns an error instead of truncating the value out of the range of `T`.
+fn as_uint<T: TryFrom<u128>>(value: &Value) -> Result<T> {
+    let n = as_u128(value)?;
+    T::try_from(n).map_err(|_| anyhow!("{} is out of range of {}", n, type_name::<T>()))
+}
+
+fn as_str(value: &Value) -> Result<&str> {
+    value
+        .as_str()
+        .ok_or_else(|| anyhow!("Expected string, got {}", value))
+}
+
+/// Returns the items of the array, the string is split by the top level commas and each
+/// item is parsed as the type returned by `item_ty`, or kept as is if there is no type.
+fn as_array<'a>(value: &Value, item_ty: impl Fn(usize) -> Option<&'a Ty>) -> Result<Vec<Value>> {
+    match value {
+        Value::Array(values) => Ok(values.clone()),
+        Value::String(s) => Ok(split_top_level(s, ',')
+            .into_iter()
+            .enumerate()
+            .map(|(i, item)| match item_ty(i) {
+                Some(ty) => parse_str(ty, item),
+                None => Value::from(item),
+            })
+            .collect()),
+        _ => Err(anyhow!("Expected array, got {}", value)),
+    }
+}
+
+/// Parses the command line string `s` into the JSON value to be encoded as `ty`.
+///
+/// The string types keep `s` as is unless it's a JSON string, so that a numeric nickname
+/// is not turned into a number. The others are parsed as JSON if possible.
+fn parse_str(ty: &Ty, s: &str) -> Value {
+    match ty {
+        Ty::Bytes | Ty::Text | Ty::AccountId | Ty::LookupSource => match serde_json::from_str(s) {
+            Ok(Value::String(s)) => Value::String(s),
+            _ => Value::from(s),
+        },
+        Ty::Option(inner) if s != "null" => parse_str(inner, s),
+        _ => serde_json::from_str(s).unwrap_or_else(|_| Value::from(s)),
+    }
+}
+
+fn decode_hex(s: &str) -> Result<Vec<u8>> {
+    Ok(hex::decode(s.strip_prefix("0x").unwrap_or(s))?)
+}
+
+fn encode_ty(metadata: &Metadata, ty: &Ty, value: &Value) -> Result<Vec<u8>> {
+    let encoded = match ty {
+        Ty::Bool => match value {
+            Value::Bool(b) => b.encode(),
+            Value::String(s) => s.parse::<bool>()?.encode(),
+            _ => return Err(anyhow!("Expected bool, got {}", value)),
+        },
+        Ty::U8 => as_uint::<u8>(value)?.encode(),
+        Ty::U16 => as_uint::<u16>(value)?.encode(),
+        Ty::U32 => as_uint::<u32>(value)?.encode(),
+        Ty::U64 => as_uint::<u64>(value)?.encode(),
+        Ty::U128 => as_u128(value)?.encode(),
+        // The compact encoding only depends on the value, the type only limits the range.
+        Ty::Compact(inner) => match **inner {
+            Ty::U8 => Compact(as_uint::<u8>(value)?).encode(),
+            Ty::U16 => Compact(as_uint::<u16>(value)?).encode(),
+            Ty::U32 => Compact(as_uint::<u32>(value)?).encode(),
+            Ty::U64 => Compact(as_uint::<u64>(value)?).encode(),
+            _ => Compact(as_u128(value)?).encode(),
+        },
+        Ty::Bytes | Ty::Text => {
+            let s = as_str(value)?;
+            if s.starts_with("0x") {
+                decode_hex(s)?.encode()
+            } else {
+                s.as_bytes().to_vec().encode()
+            }
+        }
+        Ty::FixedBytes(len) => {
+            let bytes = decode_hex(as_str(value)?)?;
+            if bytes.len() != *len {
+                return Err(anyhow!("Expected {} bytes, got {}", len, bytes.len()));
+            }
+            bytes
+        }
+        Ty::AccountId => parse_account(as_str(value)?)?.encode(),
+        Ty::LookupSource => Address::from(parse_account(as_str(value)?)?).encode(),
+        Ty::Call => match value {
+            Value::String(s) => decode_hex(s)?,
+            Value::Object(call) => {
+                let field = |name: &str| {
+                    call.get(name)
+                        .ok_or_else(|| anyhow!("Missing field `{}` of call", name))
+                };
+                let args = as_array(field("args")?, |_| None)?
+                    .into_iter()
+                    .map(|arg| match arg {
+                        Value::String(s) => s,
+                        other => other.to_string(),
+                    })
+                    .collect::<Vec<_>>();
+                metadata.encode_call(as_str(field("module")?)?, as_str(field("call")?)?, &args)?
+            }
+            _ => return Err(anyhow!("Expected call, got {}", value)),
+        },
+        Ty::Vec(inner) => {
+            let values = as_array(value, |_| Some(&**inner))?;
+            let mut encoded = Compact(values.len() as u32).encode();
+            for value in &values {
+                encoded.extend(encode_ty(metadata, inner, value)?);
+            }
+            encoded
+        }
+        Ty::Option(inner) => match value {
+            Value::Null => vec![0],
+            Value::String(s) if s.eq_ignore_ascii_case("none") => vec![0],
+            value => {
+                let mut encoded = vec![1];
+                encoded.extend(encode_ty(metadata, inner, value)?);
+                encoded
+            }
+        },
+        Ty::Tuple(types) => {
+            let values = as_array(value, |i| types.get(i))?;
+            if values.len() != types.len() {
+                return Err(anyhow!(
+                    "Expected tuple of {} items, got {}",
+                    types.len(),
+                    values.len()
+                ));
+            }
+            let mut encoded = Vec::new();
+            for (ty, value) in types.iter().zip(&values) {
+                encoded.extend(encode_ty(metadata, ty, value)?);
+            }
+            encoded
+        }
+        Ty::Enum(variants) => {
+            let index = match value {
+                Value::String(s) => variants
+                    .iter()
+                    .position(|variant| variant.eq_ignore_ascii_case(s))
+                    .ok_or_else(|| anyhow!("Unknown variant {}, expected {:?}", s, variants))?,
+                value => {
+                    let index = as_uint::<usize>(value)?;
+                    if index >= variants.len() {
+                        return Err(anyhow!(
+                            "Invalid variant index {}, expected {:?}",
+                            index,
+                            variants
+                        ));
+                    }
+                    index
+                }
+            };
+            vec![index as u8]
+        }
+        Ty::Struct(fields) => {
+            let object = value
+                .as_object()
+                .ok_or_else(|| anyhow!("Expected object, got {}", value))?;
+            let mut encoded = Vec::new();
+            for (name, ty) in fields.iter() {
+                let value = object
+                    .get(*name)
+                    .ok_or_else(|| anyhow!("Missing field `{}`", name))?;
+                encoded.extend(encode_value(metadata, ty, value)?);
+            }
+            encoded
+        }
+        Ty::Map(_, _) => return Err(anyhow!("Encoding BTreeMap is not supported")),
+    };
+    Ok(encoded)
+}
+
+fn decode_ty(metadata: &Metadata, ty: &Ty, input: &mut &[u8]) -> Result<Value> {
+    let value = match ty {
+        Ty::Bool => Value::from(bool::decode(input)?),
+        Ty::U8 => Value::from(u8::decode(input)?),
+        Ty::U16 => Value::from(u16::decode(input)?),
+        Ty::U32 => Value::from(u32::decode(input)?),
+        Ty::U64 => Value::from(u64::decode(input)?),
+        Ty::U128 => Value::from(u128::decode(input)?.to_string()),
+        Ty::Compact(inner) => {
+            let value = <Compact<u128>>::decode(input)?.0;
+            if **inner == Ty::U128 {
+                Value::from(value.to_string())
+            } else {
+                Value::from(value as u64)
+            }
+        }
+        Ty::Bytes => Value::from(format!("0x{}", hex::encode(Vec::<u8>::decode(input)?))),
+        Ty::Text => Value::from(String::from_utf8_lossy(&Vec::<u8>::decode(input)?).to_string()),
+        Ty::FixedBytes(len) => {
+            if input.len() < *len {
+                return Err(anyhow!("Not enough data to decode {} bytes", len));
+            }
+            let (bytes, rest) = input.split_at(*len);
+            *input = rest;
+            Value::from(format!("0x{}", hex::encode(bytes)))
+        }
+        Ty::AccountId => Value::from(AccountId::decode(input)?.to_ss58check()),
+        Ty::LookupSource => match Address::decode(input)? {
+            Address::Id(who) => Value::from(who.to_ss58check()),
+            Address::Index(index) => Value::from(index),
+        },
+        Ty::Call => serde_json::to_value(metadata.decode_call(input)?)?,
+        Ty::Vec(inner) => {
+            let len = <Compact<u32>>::decode(input)?.0;
+            // The length is not trusted, each item takes at least one byte unless it's empty.
+            let mut values = Vec::with_capacity((len as usize).min(input.len()));
+            for _ in 0..len {
+                values.push(decode_ty(metadata, inner, input)?);
+            }
+            Value::Array(values)
+        }
+        Ty::Option(inner) => match u8::decode(input)? {
+            0 => Value::Null,
+            1 => decode_ty(metadata, inner, input)?,
+            b => return Err(anyhow!("Invalid Option prefix: {}", b)),
+        },
+        Ty::Tuple(types) => Value::Array(
+            types
+                .iter()
+                .map(|ty| decode_ty(metadata, ty, input))
+                .collect::<Result<_>>()?,
+        ),
+        Ty::Map(key, value) => {
+            let len = <Compact<u32>>::decode(input)?.0;
+            let mut map = Map::new();
+            for _ in 0..len {
+                let k = match decode_ty(metadata, key, input)? {
+                    Value::String(s) => s,
+                    other => other.to_string(),
+                };
+                map.insert(k, decode_ty(metadata, value, input)?);
+            }
+            Value::Object(map)
+        }
+        Ty::Enum(variants) => {
+            let index = u8::decode(input)? as usize;
+            let variant = variants
+                .get(index)
+                .ok_or_else(|| anyhow!("Invalid variant index {}", index))?;
+            Value::from(*variant)
+        }
+        Ty::Struct(fields) => {
+            let mut object = Map::new();
+            for (name, ty) in fields.iter() {
+                object.insert((*name).into(), decode_value(metadata, ty, input)?);
+            }
+            Value::Object(object)
+        }
+    };
+    Ok(value)
+}
+
+/// Returns true if the type names `a` and `b` are encoded in the same way, e.g.,
+/// `T::Balance` and `BalanceOf<T>`.
+///
+/// The unsupported types are compared by the names without the path qualifiers.
+pub fn same_type(a: &str, b: &str) -> bool {
+    match (parse_type(a), parse_type(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => strip_path(a).replace(' ', "") == strip_path(b).replace(' ', ""),
+    }
+}
+
+/// Encodes `value` as the type named `ty`.
+///
+/// The strings are parsed according to the type, e.g., the account type accepts
+/// the SS58 address and the number accepts both the JSON number and the string.
+pub fn encode_value(metadata: &Metadata, ty: &str, value: &Value) -> Result<Vec<u8>> {
+    encode_ty(metadata, &parse_type(ty)?, value)
+}
+
+/// Encodes the command line string `s` as the type named `ty`, `s` is parsed according
+/// to the type, e.g., JSON for the numbers and collections, as is for the strings.
+pub fn encode_str(metadata: &Metadata, ty: &str, s: &str) -> Result<Vec<u8>> {
+    let ty = parse_type(ty)?;
+    encode_ty(metadata, &ty, &parse_str(&ty, s))
+}
+
+/// Decodes the type named `ty` from `input` into JSON.
+pub fn decode_value(metadata: &Metadata, ty: &str, input: &mut &[u8]) -> Result<Value> {
+    decode_ty(metadata, &parse_type(ty)?, input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty_metadata() -> Metadata {
+        Metadata {
+            modules: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_strip_path() {
+        assert_eq!(strip_path("T::AccountId"), "AccountId");
+        assert_eq!(strip_path("<T as Trait>::Call"), "Call");
+        assert_eq!(strip_path("<T::Lookup as StaticLookup>::Source"), "Source");
+        assert_eq!(strip_path("Vec<T::AccountId>"), "Vec<T::AccountId>");
+        assert_eq!(strip_path("BalanceOf<T>"), "BalanceOf<T>");
+    }
+
+    #[test]
+    fn test_parse_type() {
+        assert_eq!(
+            parse_type("Compact<BalanceOf<T>>").unwrap(),
+            Ty::Compact(Box::new(Ty::U128))
+        );
+        assert_eq!(parse_type("Vec<u8>").unwrap(), Ty::Bytes);
+        assert_eq!(parse_type("Box<<T as Trait>::Call>").unwrap(), Ty::Call);
+        assert_eq!(
+            parse_type("Vec<(T::AccountId, BalanceOf<T>)>").unwrap(),
+            Ty::Vec(Box::new(Ty::Tuple(vec![Ty::AccountId, Ty::U128])))
+        );
+        assert_eq!(parse_type("[u8; 8]").unwrap(), Ty::FixedBytes(8));
+        assert!(parse_type("SomethingUnknown").is_err());
+    }
+
+    #[test]
+    fn test_encode_decode_roundtrip() {
+        let metadata = empty_metadata();
+        let cases = vec![
+            ("Compact<u32>", Value::from(10)),
+            (
+                "BalanceOf<T>",
+                Value::from("340282366920938463463374607431768211455"),
+            ),
+            ("Option<T::BlockNumber>", Value::Null),
+            ("Option<T::BlockNumber>", Value::from(100)),
+            ("Vec<u8>", Value::from("0x0102")),
+            ("ReferralId", Value::from("ChainX")),
+        ];
+        for (ty, value) in cases {
+            let encoded = encode_value(&metadata, ty, &value).unwrap();
+            let decoded = decode_value(&metadata, ty, &mut encoded.as_slice()).unwrap();
+            assert_eq!(decoded, value, "type: {}", ty);
+        }
+    }
+
+    #[test]
+    fn test_encode_out_of_range() {
+        let metadata = empty_metadata();
+        assert_eq!(
+            encode_value(&metadata, "u8", &Value::from(255)).unwrap(),
+            vec![255]
+        );
+        assert!(encode_value(&metadata, "u8", &Value::from(256)).is_err());
+        assert!(encode_value(&metadata, "u32", &Value::from("4294967296")).is_err());
+        assert!(encode_value(&metadata, "Compact<u32>", &Value::from("4294967296")).is_err());
+        assert_eq!(
+            encode_value(&metadata, "AssetType", &Value::from(4)).unwrap(),
+            vec![4]
+        );
+        assert!(encode_value(&metadata, "AssetType", &Value::from(5)).is_err());
+    }
+
+    #[test]
+    fn test_encode_str() {
+        let metadata = empty_metadata();
+        // A numeric nickname is kept as the string.
+        assert_eq!(
+            encode_str(&metadata, "ReferralId", "123").unwrap(),
+            b"123".to_vec().encode()
+        );
+        assert_eq!(
+            encode_str(&metadata, "ReferralId", "\"123\"").unwrap(),
+            b"123".to_vec().encode()
+        );
+        assert_eq!(
+            encode_str(&metadata, "Option<ReferralId>", "123").unwrap(),
+            Some(b"123".to_vec()).encode()
+        );
+        assert_eq!(
+            encode_str(&metadata, "Vec<ReferralId>", "1, 2").unwrap(),
+            vec![b"1".to_vec(), b"2".to_vec()].encode()
+        );
+        assert_eq!(
+            encode_str(&metadata, "(u32, ReferralId)", "1, 2").unwrap(),
+            (1u32, b"2".to_vec()).encode()
+        );
+        assert_eq!(
+            encode_str(&metadata, "Compact<u32>", "10").unwrap(),
+            Compact(10u32).encode()
+        );
+    }
+
+    #[test]
+    fn test_decode_vec_huge_len() {
+        let metadata = empty_metadata();
+        // Compact length 0x3fffffff followed by only 2 items.
+        let encoded = [0xfe, 0xff, 0xff, 0xff, 1, 2];
+        assert!(decode_value(&metadata, "Vec<u8>", &mut &encoded[..]).is_err());
+        assert!(decode_value(&metadata, "Vec<u32>", &mut &encoded[..]).is_err());
+        assert!(decode_value(&metadata, "Vec<T::AccountId>", &mut &encoded[..]).is_err());
+    }
+
+    #[test]
+    fn test_decode_map() {
+        let metadata = empty_metadata();
+        let mut map = std::collections::BTreeMap::new();
+        map.insert(0u8, 100u128);
+        map.insert(2u8, 5u128);
+        let encoded = map.encode();
+        let decoded = decode_value(
+            &metadata,
+            "BTreeMap<AssetType, BalanceOf<T>>",
+            &mut encoded.as_slice(),
+        )
+        .unwrap();
+        assert_eq!(
+            decoded,
+            serde_json::json!({"Usable": "100", "Reserved": "5"})
+        );
+    }
+
+    #[test]
+    fn test_same_type() {
+        assert!(same_type("T::Balance", "BalanceOf<T>"));
+        assert!(same_type(
+            "<T::Lookup as StaticLookup>::Source",
+            "LookupSource"
+        ));
+        assert!(same_type(
+            "ValidatorProfile<T::BlockNumber>",
+            "ValidatorProfile<BlockNumber>"
+        ));
+        assert!(same_type(
+            "Compact<UnbondedIndex>",
+            "Compact<UnbondedIndex>"
+        ));
+        assert!(!same_type("Compact<BalanceOf<T>>", "BalanceOf<T>"));
+        assert!(!same_type("Compact<UnbondedIndex>", "UnbondedIndex"));
+    }
+
+    #[test]
+    fn test_decode_struct() {
+        let metadata = empty_metadata();
+        // registered_at: 10, is_chilled: true, last_chilled: Some(20), referral_id: "ChainX"
+        let encoded = (10u32, true, Some(20u32), b"ChainX".to_vec()).encode();
+        let decoded = decode_value(
+            &metadata,
+            "ValidatorProfile<T::BlockNumber>",
+            &mut encoded.as_slice(),
+        )
+        .unwrap();
+        assert_eq!(
+            decoded,
+            serde_json::json!({
+                "registered_at": 10,
+                "is_chilled": true,
+                "last_chilled": 20,
+                "referral_id": "ChainX",
+            })
+        );
+    }
+}
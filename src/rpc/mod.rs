@@ -1,68 +1,489 @@
+pub mod payment;
+pub mod storage;
 pub mod system;
 pub mod xassets;
 pub mod xmining_asset;
 pub mod xstaking;
 
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
 
 use anyhow::{anyhow, Result};
+use async_std::{sync::Mutex, task};
 use codec::Decode;
 use jsonrpsee::{
+    client::RequestError,
     common::{to_value as to_json_value, Params},
     Client,
 };
+use serde::de::DeserializeOwned;
 use sp_core::{
-    storage::{StorageData, StorageKey},
-    twox_128,
+    storage::{StorageChangeSet, StorageData, StorageKey},
+    twox_128, Bytes,
 };
-use subxt::system::AccountInfo;
+use sp_version::RuntimeVersion;
+use subxt::system::{AccountInfo, System};
 
-use crate::runtime::{
-    primitives::{AccountId, AssetId, Balance, BlockNumber, Hash},
-    ChainXRuntime,
+use crate::{
+    metadata::Metadata,
+    runtime::{
+        primitives::{AccountId, AssetId, Balance, BlockNumber, Hash, Index},
+        ChainXRuntime,
+    },
 };
 
-const TWOX_HASH_LEN: usize = 16; // 8 bytes hex
-const BLAKE_HASH_LEN: usize = 32; // 16 bytes hex
-const STORAGE_PREFIX_LEN: usize = 64; // 32 bytes hex
+pub type Header = <ChainXRuntime as System>::Header;
 
-fn storage_prefix_for(module: &str, storage_name: &str) -> Vec<u8> {
+/// Length of the storage prefix in bytes, i.e., `twox_128(module) ++ twox_128(storage_name)`.
+const STORAGE_PREFIX_LEN: usize = 32;
+
+pub fn storage_prefix_for(module: &str, storage_name: &str) -> Vec<u8> {
     let mut storage_prefix = twox_128(module.as_bytes()).to_vec();
     storage_prefix.extend_from_slice(&twox_128(storage_name.as_bytes()));
     storage_prefix
 }
 
+/// Default number of the storage keys fetched in one page.
+pub const DEFAULT_PAGE_SIZE: u32 = 1000;
+
+/// Max attempts of an RPC call, including the first one.
+const MAX_ATTEMPTS: u32 = 5;
+/// Delay before the first retry, doubled for each of the following retries.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Client of the RPCs not provided by subxt.
+///
+/// All the RPCs here are idempotent, a call failed by the connection is retried with backoff
+/// after reconnecting to the next url, so that the long running bulk getters survive a
+/// disconnection.
 #[derive(Clone)]
 pub struct Rpc {
+    urls: Arc<Vec<String>>,
+    client: Arc<Mutex<Connection>>,
+    page_size: u32,
+}
+
+/// The connected client of `Rpc`.
+struct Connection {
     client: Client,
+    /// Index of the url connected to.
+    index: usize,
+    /// Number of the reconnections before this connection.
+    generation: u64,
+}
+
+/// Connects to the first reachable url, starting from `urls[start]`.
+async fn connect(urls: &[String], start: usize) -> Result<(Client, usize)> {
+    let mut errors = Vec::new();
+    for offset in 0..urls.len() {
+        let index = (start + offset) % urls.len();
+        match jsonrpsee::ws_client(&urls[index]).await {
+            Ok(client) => return Ok((client, index)),
+            Err(err) => errors.push(format!("{}: {}", urls[index], err)),
+        }
+    }
+    if errors.is_empty() {
+        return Err(anyhow!("No url of ChainX node is given"));
+    }
+    Err(anyhow!(
+        "Failed to connect to ChainX node, {}",
+        errors.join(", ")
+    ))
 }
 
 impl Rpc {
-    pub async fn new<U: AsRef<str>>(url: U) -> Result<Self> {
-        let client = jsonrpsee::ws_client(url.as_ref()).await?;
-        Ok(Self { client })
+    /// Connects to the first reachable url of `urls`, the others are used for failover.
+    pub async fn new<I, U>(urls: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = U>,
+        U: AsRef<str>,
+    {
+        let urls = urls
+            .into_iter()
+            .map(|url| url.as_ref().to_string())
+            .collect::<Vec<_>>();
+        let (client, index) = connect(&urls, 0).await?;
+        Ok(Self {
+            urls: Arc::new(urls),
+            client: Arc::new(Mutex::new(Connection {
+                client,
+                index,
+                generation: 0,
+            })),
+            page_size: DEFAULT_PAGE_SIZE,
+        })
+    }
+
+    /// Calls the RPC `method`, retries it with backoff if the connection fails.
+    async fn request<T: DeserializeOwned>(&self, method: &str, params: Params) -> Result<T> {
+        let mut backoff = INITIAL_BACKOFF;
+        let mut attempt = 1;
+        loop {
+            let (client, index, generation) = {
+                let connection = self.client.lock().await;
+                (
+                    connection.client.clone(),
+                    connection.index,
+                    connection.generation,
+                )
+            };
+            match client.request(method, params.clone()).await {
+                Ok(value) => return Ok(value),
+                Err(RequestError::TransportError(err)) if attempt < MAX_ATTEMPTS => {
+                    eprintln!(
+                        "RPC {} to {} failed: {}, retrying in {:?}",
+                        method, self.urls[index], err, backoff
+                    );
+                    task::sleep(backoff).await;
+                    backoff *= 2;
+                    attempt += 1;
+                    self.reconnect(generation).await;
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    /// Replaces the failed connection of `generation` with a new one, starting from the
+    /// next url.
+    ///
+    /// Nothing is done if another call has already reconnected. The connection error is
+    /// ignored as the retry will fail and reconnect again.
+    async fn reconnect(&self, generation: u64) {
+        let mut connection = self.client.lock().await;
+        if connection.generation != generation {
+            return;
+        }
+        match connect(&self.urls, connection.index + 1).await {
+            Ok((client, index)) => {
+                *connection = Connection {
+                    client,
+                    index,
+                    generation: generation + 1,
+                }
+            }
+            Err(err) => eprintln!("{}", err),
+        }
+    }
+
+    /// Returns the client of the current connection, e.g., to build the subxt client on it.
+    pub async fn connection(&self) -> Client {
+        self.client.lock().await.client.clone()
+    }
+
+    /// Returns the generation of the current connection, which is increased by every
+    /// reconnection, so that the clients built on a failed connection can be rebuilt.
+    pub async fn generation(&self) -> u64 {
+        self.client.lock().await.generation
+    }
+
+    /// Sets the number of the storage keys fetched in one page by the bulk getters.
+    pub fn with_page_size(mut self, page_size: u32) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /// Returns the hash of the best block.
+    pub async fn best_hash(&self) -> Result<Hash> {
+        let hash: Option<Hash> = self.request("chain_getBlockHash", Params::None).await?;
+        hash.ok_or_else(|| anyhow!("Best block hash not found"))
     }
 
     pub async fn genesis_hash(&self) -> Result<Hash> {
         let params = Params::Array(vec![to_json_value(0)?]);
-        let hash = self.client.request("chain_getBlockHash", params).await?;
+        let hash = self.request("chain_getBlockHash", params).await?;
         Ok(hash)
     }
 
-    #[allow(unused)]
+    /// Returns the hash of block `number`, `None` if the block has not been produced.
+    pub async fn block_hash(&self, number: BlockNumber) -> Result<Option<Hash>> {
+        let params = Params::Array(vec![to_json_value(number)?]);
+        let hash = self.request("chain_getBlockHash", params).await?;
+        Ok(hash)
+    }
+
+    pub async fn finalized_head(&self) -> Result<Hash> {
+        let hash = self.request("chain_getFinalizedHead", Params::None).await?;
+        Ok(hash)
+    }
+
+    /// Returns the header of the block, the best block if `hash` is `None`.
+    pub async fn header(&self, hash: Option<Hash>) -> Result<Option<Header>> {
+        let params = Params::Array(vec![to_json_value(hash)?]);
+        let header = self.request("chain_getHeader", params).await?;
+        Ok(header)
+    }
+
+    pub async fn runtime_version(&self, hash: Option<Hash>) -> Result<RuntimeVersion> {
+        let params = Params::Array(vec![to_json_value(hash)?]);
+        let version = self.request("state_getRuntimeVersion", params).await?;
+        Ok(version)
+    }
+
+    pub async fn metadata(&self, hash: Option<Hash>) -> Result<Metadata> {
+        let params = Params::Array(vec![to_json_value(hash)?]);
+        let bytes: Bytes = self.request("state_getMetadata", params).await?;
+        Metadata::decode(&bytes)
+    }
+
+    /// Returns at most `count` storage keys under `prefix` after `start_key`.
+    pub async fn get_keys_paged(
+        &self,
+        prefix: &StorageKey,
+        count: u32,
+        start_key: Option<&StorageKey>,
+        hash: Option<Hash>,
+    ) -> Result<Vec<StorageKey>> {
+        let params = Params::Array(vec![
+            to_json_value(prefix)?,
+            to_json_value(count)?,
+            to_json_value(start_key)?,
+            to_json_value(hash)?,
+        ]);
+        let keys = self.request("state_getKeysPaged", params).await?;
+        Ok(keys)
+    }
+
+    /// Returns the storage values of `keys`, `None` if the key does not exist.
+    pub async fn query_storage_at(
+        &self,
+        keys: &[StorageKey],
+        hash: Option<Hash>,
+    ) -> Result<Vec<(StorageKey, Option<StorageData>)>> {
+        let params = Params::Array(vec![to_json_value(keys)?, to_json_value(hash)?]);
+        let change_sets: Vec<StorageChangeSet<Hash>> =
+            self.request("state_queryStorageAt", params).await?;
+        Ok(change_sets
+            .into_iter()
+            .flat_map(|change_set| change_set.changes)
+            .collect())
+    }
+
+    /// Returns the paged iterator of the storage pairs under `prefix`.
+    ///
+    /// The pages are all fetched at the same block, the best block if `hash` is `None`.
+    pub fn iter_pairs(&self, prefix: StorageKey, hash: Option<Hash>) -> StoragePairs<'_> {
+        StoragePairs {
+            rpc: self,
+            prefix,
+            hash,
+            start_key: None,
+            finished: false,
+        }
+    }
+
+    /// Returns all the storage keys under `key`, fetched by `state_getKeysPaged`.
     pub async fn get_keys(&self, key: StorageKey, hash: Option<Hash>) -> Result<Vec<StorageKey>> {
-        let params = Params::Array(vec![to_json_value(key)?, to_json_value(hash)?]);
-        let data = self.client.request("state_getKeys", params).await?;
-        Ok(data)
+        let hash = match hash {
+            Some(hash) => hash,
+            None => self.best_hash().await?,
+        };
+        let mut keys = Vec::new();
+        loop {
+            let page = self
+                .get_keys_paged(&key, self.page_size, keys.last(), Some(hash))
+                .await?;
+            let finished = page.len() < self.page_size as usize;
+            keys.extend(page);
+            if finished {
+                return Ok(keys);
+            }
+        }
     }
 
+    /// Returns all the storage pairs under `key`.
+    ///
+    /// Unlike `state_getPairs`, which is unsafe and usually disabled on the public nodes,
+    /// the pairs are fetched page by page with `state_getKeysPaged` and `state_queryStorageAt`.
     pub async fn get_pairs(
         &self,
         key: StorageKey,
         hash: Option<Hash>,
     ) -> Result<Vec<(StorageKey, StorageData)>> {
-        let params = Params::Array(vec![to_json_value(key)?, to_json_value(hash)?]);
-        let data = self.client.request("state_getPairs", params).await?;
-        Ok(data)
+        let mut pairs = Vec::new();
+        let mut iter = self.iter_pairs(key, hash);
+        while let Some(page) = iter.next_page().await? {
+            pairs.extend(page);
+        }
+        Ok(pairs)
+    }
+}
+
+/// Paged iterator of the storage pairs under a prefix.
+pub struct StoragePairs<'a> {
+    rpc: &'a Rpc,
+    prefix: StorageKey,
+    hash: Option<Hash>,
+    start_key: Option<StorageKey>,
+    finished: bool,
+}
+
+impl StoragePairs<'_> {
+    /// Returns the next page of the storage pairs, `None` if all the pairs have been returned.
+    pub async fn next_page(&mut self) -> Result<Option<Vec<(StorageKey, StorageData)>>> {
+        if self.finished {
+            return Ok(None);
+        }
+        let hash = match self.hash {
+            Some(hash) => hash,
+            None => {
+                let hash = self.rpc.best_hash().await?;
+                self.hash = Some(hash);
+                hash
+            }
+        };
+
+        let keys = self
+            .rpc
+            .get_keys_paged(
+                &self.prefix,
+                self.rpc.page_size,
+                self.start_key.as_ref(),
+                Some(hash),
+            )
+            .await?;
+        self.finished = keys.len() < self.rpc.page_size as usize;
+        self.start_key = keys.last().cloned();
+        if keys.is_empty() {
+            return Ok(None);
+        }
+
+        let pairs = self
+            .rpc
+            .query_storage_at(&keys, Some(hash))
+            .await?
+            .into_iter()
+            .filter_map(|(key, value)| value.map(|value| (key, value)))
+            .collect();
+        Ok(Some(pairs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use async_std::net::TcpListener;
+    use async_tungstenite::tungstenite::Message;
+    use futures::{SinkExt, StreamExt};
+
+    /// Spawns a mock node answering every request with `hash`, each connection is dropped
+    /// without closing after serving `served_per_connection` requests.
+    async fn mock_node(hash: Hash, served_per_connection: usize) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        task::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                task::spawn(async move {
+                    let mut ws = match async_tungstenite::accept_async(stream).await {
+                        Ok(ws) => ws,
+                        Err(_) => return,
+                    };
+                    let mut served = 0;
+                    while let Some(Ok(message)) = ws.next().await {
+                        if served == served_per_connection {
+                            return;
+                        }
+                        let request: serde_json::Value = match message {
+                            Message::Text(text) => serde_json::from_str(&text).unwrap(),
+                            _ => continue,
+                        };
+                        let response = serde_json::json!({
+                            "jsonrpc": "2.0",
+                            "id": request["id"],
+                            "result": hash,
+                        });
+                        ws.send(Message::Text(response.to_string())).await.unwrap();
+                        served += 1;
+                    }
+                });
+            }
+        });
+        url
+    }
+
+    /// Returns the url of a port nothing listens on.
+    async fn dead_url() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        format!("ws://{}", listener.local_addr().unwrap())
+    }
+
+    #[async_std::test]
+    async fn test_failover_on_connect() {
+        let hash = Hash::repeat_byte(1);
+        let urls = vec![dead_url().await, mock_node(hash, usize::MAX).await];
+        let rpc = Rpc::new(&urls).await.unwrap();
+        assert_eq!(rpc.best_hash().await.unwrap(), hash);
+    }
+
+    #[async_std::test]
+    async fn test_retry_on_disconnect() {
+        let hash1 = Hash::repeat_byte(1);
+        let hash2 = Hash::repeat_byte(2);
+        let urls = vec![
+            mock_node(hash1, 1).await,
+            mock_node(hash2, usize::MAX).await,
+        ];
+        let rpc = Rpc::new(&urls).await.unwrap();
+        assert_eq!(rpc.best_hash().await.unwrap(), hash1);
+        // The first node drops the connection, the call is retried on the second one.
+        assert_eq!(rpc.best_hash().await.unwrap(), hash2);
+        assert_eq!(rpc.best_hash().await.unwrap(), hash2);
+    }
+
+    #[async_std::test]
+    async fn test_retry_on_disconnect_in_loop() {
+        let hash1 = Hash::repeat_byte(1);
+        let hash2 = Hash::repeat_byte(2);
+        let urls = vec![
+            mock_node(hash1, 2).await,
+            mock_node(hash2, usize::MAX).await,
+        ];
+        let rpc = Rpc::new(&urls).await.unwrap();
+        let key = StorageKey(storage_prefix_for("System", "Account"));
+        // The first node drops the connection in the middle of the per-account reads,
+        // the rest are served by the second one.
+        let mut values = Vec::new();
+        for _ in 0..5 {
+            values.push(rpc.get_storage_value::<Hash>(&key, None).await.unwrap());
+        }
+        assert_eq!(
+            values,
+            [vec![Some(hash1); 2], vec![Some(hash2); 3]].concat()
+        );
+    }
+
+    #[async_std::test]
+    async fn test_connection_after_failover() {
+        let hash1 = Hash::repeat_byte(1);
+        let hash2 = Hash::repeat_byte(2);
+        let urls = vec![
+            mock_node(hash1, 1).await,
+            mock_node(hash2, usize::MAX).await,
+        ];
+        let rpc = Rpc::new(&urls).await.unwrap();
+        let stale = rpc.connection().await;
+        assert_eq!(rpc.best_hash().await.unwrap(), hash1);
+        assert_eq!(rpc.generation().await, 0);
+
+        // The client built on the failed connection keeps failing, the one built after
+        // the failover is connected to the second node.
+        assert_eq!(rpc.best_hash().await.unwrap(), hash2);
+        assert_eq!(rpc.generation().await, 1);
+        let result: Result<Option<Hash>, _> =
+            stale.request("chain_getBlockHash", Params::None).await;
+        assert!(result.is_err());
+        let hash: Option<Hash> = rpc
+            .connection()
+            .await
+            .request("chain_getBlockHash", Params::None)
+            .await
+            .unwrap();
+        assert_eq!(hash, Some(hash2));
+    }
+
+    #[async_std::test]
+    async fn test_all_urls_unreachable() {
+        let urls = vec![dead_url().await, dead_url().await];
+        assert!(Rpc::new(&urls).await.is_err());
     }
 }
@@ -0,0 +1,57 @@
+use super::*;
+
+use frame_support::weights::{DispatchClass, Weight};
+use serde::{de, Deserialize, Serialize};
+
+use crate::serde::serde_num_str;
+
+/// Information related to the dispatch of an extrinsic, returned by `payment_queryInfo`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeDispatchInfo {
+    /// Weight of this dispatch.
+    pub weight: Weight,
+    /// Class of this dispatch.
+    pub class: DispatchClass,
+    /// The inclusion fee of this dispatch, not including the tip.
+    #[serde(
+        serialize_with = "serde_num_str::serialize",
+        deserialize_with = "deserialize_balance"
+    )]
+    pub partial_fee: Balance,
+}
+
+/// The balance may be returned as either a number or a string, depending on the node.
+fn deserialize_balance<'de, D>(deserializer: D) -> std::result::Result<Balance, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Number(n) => n
+            .as_u64()
+            .map(Balance::from)
+            .ok_or_else(|| de::Error::custom("Invalid balance number")),
+        serde_json::Value::String(s) => match s.strip_prefix("0x") {
+            Some(hex) => Balance::from_str_radix(hex, 16),
+            None => s.parse::<Balance>(),
+        }
+        .map_err(|_| de::Error::custom("Invalid balance string")),
+        _ => Err(de::Error::custom("Expected a number or a string")),
+    }
+}
+
+impl Rpc {
+    /// Returns the dispatch info of the SCALE encoded extrinsic.
+    pub async fn query_info(
+        &self,
+        extrinsic: &[u8],
+        hash: Option<Hash>,
+    ) -> Result<RuntimeDispatchInfo> {
+        let params = Params::Array(vec![
+            to_json_value(Bytes(extrinsic.to_vec()))?,
+            to_json_value(hash)?,
+        ]);
+        let info = self.request("payment_queryInfo", params).await?;
+        Ok(info)
+    }
+}
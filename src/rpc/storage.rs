@@ -0,0 +1,333 @@
+use super::*;
+
+use std::marker::PhantomData;
+
+use codec::Encode;
+use sp_core::{blake2_128, blake2_256, twox_128, twox_256, twox_64};
+
+/// Hasher of the storage map keys.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StorageHasher {
+    Identity,
+    Twox64Concat,
+    Blake2_128Concat,
+    Blake2_128,
+    Blake2_256,
+    Twox128,
+    Twox256,
+}
+
+impl From<frame_metadata::StorageHasher> for StorageHasher {
+    fn from(hasher: frame_metadata::StorageHasher) -> Self {
+        use frame_metadata::StorageHasher as Hasher;
+        match hasher {
+            Hasher::Identity => Self::Identity,
+            Hasher::Twox64Concat => Self::Twox64Concat,
+            Hasher::Blake2_128Concat => Self::Blake2_128Concat,
+            Hasher::Blake2_128 => Self::Blake2_128,
+            Hasher::Blake2_256 => Self::Blake2_256,
+            Hasher::Twox128 => Self::Twox128,
+            Hasher::Twox256 => Self::Twox256,
+        }
+    }
+}
+
+impl StorageHasher {
+    /// Length of the hash prepended to the SCALE encoded key.
+    pub fn hash_len(self) -> usize {
+        match self {
+            Self::Identity => 0,
+            Self::Twox64Concat => 8,
+            Self::Blake2_128Concat | Self::Blake2_128 | Self::Twox128 => 16,
+            Self::Blake2_256 | Self::Twox256 => 32,
+        }
+    }
+
+    /// Returns whether the encoded key follows the hash, i.e., the key can be decoded.
+    pub fn is_concat(self) -> bool {
+        matches!(
+            self,
+            Self::Identity | Self::Twox64Concat | Self::Blake2_128Concat
+        )
+    }
+
+    /// Returns the hashed key, i.e., the hash followed by the encoded key for the concat hashers.
+    pub fn hash(self, encoded: &[u8]) -> Vec<u8> {
+        let mut hashed = match self {
+            Self::Identity => Vec::new(),
+            Self::Twox64Concat => twox_64(encoded).to_vec(),
+            Self::Blake2_128Concat => blake2_128(encoded).to_vec(),
+            Self::Blake2_128 => return blake2_128(encoded).to_vec(),
+            Self::Blake2_256 => return blake2_256(encoded).to_vec(),
+            Self::Twox128 => return twox_128(encoded).to_vec(),
+            Self::Twox256 => return twox_256(encoded).to_vec(),
+        };
+        hashed.extend_from_slice(encoded);
+        hashed
+    }
+
+    /// Skips the hash, leaving the encoded key at the beginning of `input`.
+    pub fn skip_hash(self, input: &mut &[u8]) -> Result<()> {
+        if !self.is_concat() {
+            return Err(anyhow!("Storage key hashed by {:?} can't be decoded", self));
+        }
+        if input.len() < self.hash_len() {
+            return Err(anyhow!("Storage key is shorter than the {:?} hash", self));
+        }
+        *input = &input[self.hash_len()..];
+        Ok(())
+    }
+
+    /// Skips the hash and decodes the key.
+    fn decode<K: Decode>(self, input: &mut &[u8]) -> Result<K> {
+        self.skip_hash(input)?;
+        Ok(K::decode(input)?)
+    }
+}
+
+/// Decodes the keys of the map from the full storage key.
+///
+/// The key of a single map is decoded as `(K1, ())` with `StorageHasher::Identity` as `hasher2`.
+pub fn decode_map_key<K1: Decode, K2: Decode>(
+    key: &[u8],
+    hasher1: StorageHasher,
+    hasher2: StorageHasher,
+) -> Result<(K1, K2)> {
+    if key.len() < STORAGE_PREFIX_LEN {
+        return Err(anyhow!("Storage key is shorter than the storage prefix"));
+    }
+    let mut input = &key[STORAGE_PREFIX_LEN..];
+    let key1 = hasher1.decode(&mut input)?;
+    let key2 = hasher2.decode(&mut input)?;
+    if !input.is_empty() {
+        return Err(anyhow!(
+            "{} trailing bytes after decoding the storage key",
+            input.len()
+        ));
+    }
+    Ok((key1, key2))
+}
+
+/// Paged iterator of a storage map with the keys and values decoded.
+///
+/// Use `()` as `K2` for a single map.
+pub struct StorageMapIter<'a, K1, K2, V> {
+    pairs: StoragePairs<'a>,
+    hasher1: StorageHasher,
+    hasher2: StorageHasher,
+    _marker: PhantomData<(K1, K2, V)>,
+}
+
+impl<K1: Decode, K2: Decode, V: Decode> StorageMapIter<'_, K1, K2, V> {
+    /// Returns the next page of the decoded entries, `None` if all the entries have been returned.
+    pub async fn next_page(&mut self) -> Result<Option<Vec<(K1, K2, V)>>> {
+        let page = match self.pairs.next_page().await? {
+            Some(page) => page,
+            None => return Ok(None),
+        };
+        let mut entries = Vec::with_capacity(page.len());
+        for (key, value) in page {
+            let (key1, key2) = decode_map_key(&key.0, self.hasher1, self.hasher2)?;
+            let value = V::decode(&mut value.0.as_slice())?;
+            entries.push((key1, key2, value));
+        }
+        Ok(Some(entries))
+    }
+}
+
+impl Rpc {
+    /// Returns the storage value of `key`, `None` if the key does not exist.
+    pub async fn get_storage(
+        &self,
+        key: &StorageKey,
+        hash: Option<Hash>,
+    ) -> Result<Option<StorageData>> {
+        let params = Params::Array(vec![to_json_value(key)?, to_json_value(hash)?]);
+        let data = self.request("state_getStorage", params).await?;
+        Ok(data)
+    }
+
+    /// Returns the decoded storage value of `key`, `None` if the key does not exist.
+    pub async fn get_storage_value<V: Decode>(
+        &self,
+        key: &StorageKey,
+        hash: Option<Hash>,
+    ) -> Result<Option<V>> {
+        match self.get_storage(key, hash).await? {
+            Some(data) => Ok(Some(V::decode(&mut data.0.as_slice())?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the value of the storage map `module::storage_name` under `key`, the key is
+    /// hashed by the hasher in `metadata`.
+    ///
+    /// Like the `Store` of subxt, the default value in `metadata` is returned if the key does
+    /// not exist, `None` is returned only for the optional storage.
+    pub async fn get_map_value<K: Encode, V: Decode>(
+        &self,
+        metadata: &Metadata,
+        module: &str,
+        storage_name: &str,
+        key: &K,
+        hash: Option<Hash>,
+    ) -> Result<Option<V>> {
+        let module = metadata.module(module)?;
+        let storage = module.storage(storage_name)?;
+        let hasher = match storage.keys().as_slice() {
+            [(hasher, _)] => *hasher,
+            _ => {
+                return Err(anyhow!(
+                    "{}::{} is not a storage map",
+                    module.name,
+                    storage.name
+                ))
+            }
+        };
+        let mut storage_key = storage_prefix_for(&module.storage_prefix, &storage.name);
+        storage_key.extend(hasher.hash(&key.encode()));
+
+        match self
+            .get_storage_value(&StorageKey(storage_key), hash)
+            .await?
+        {
+            Some(value) => Ok(Some(value)),
+            None if storage.is_optional => Ok(None),
+            None => Ok(Some(V::decode(&mut storage.default.as_slice())?)),
+        }
+    }
+
+    /// Returns the paged iterator of the storage map `module::storage_name`.
+    pub fn iter_map<K: Decode, V: Decode>(
+        &self,
+        module: &str,
+        storage_name: &str,
+        hasher: StorageHasher,
+        hash: Option<Hash>,
+    ) -> StorageMapIter<'_, K, (), V> {
+        self.iter_double_map(module, storage_name, hasher, StorageHasher::Identity, hash)
+    }
+
+    /// Returns the paged iterator of the storage double map `module::storage_name`.
+    pub fn iter_double_map<K1: Decode, K2: Decode, V: Decode>(
+        &self,
+        module: &str,
+        storage_name: &str,
+        hasher1: StorageHasher,
+        hasher2: StorageHasher,
+        hash: Option<Hash>,
+    ) -> StorageMapIter<'_, K1, K2, V> {
+        let prefix = StorageKey(storage_prefix_for(module, storage_name));
+        StorageMapIter {
+            pairs: self.iter_pairs(prefix, hash),
+            hasher1,
+            hasher2,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns all the entries of the storage map `module::storage_name`.
+    pub async fn get_map<K: Decode + Ord, V: Decode>(
+        &self,
+        module: &str,
+        storage_name: &str,
+        hasher: StorageHasher,
+        hash: Option<Hash>,
+    ) -> Result<BTreeMap<K, V>> {
+        let mut map = BTreeMap::new();
+        let mut iter = self.iter_map::<K, V>(module, storage_name, hasher, hash);
+        while let Some(page) = iter.next_page().await? {
+            map.extend(page.into_iter().map(|(key, (), value)| (key, value)));
+        }
+        Ok(map)
+    }
+
+    /// Returns all the entries of the storage double map `module::storage_name`.
+    pub async fn get_double_map<K1: Decode + Ord, K2: Decode + Ord, V: Decode>(
+        &self,
+        module: &str,
+        storage_name: &str,
+        hasher1: StorageHasher,
+        hasher2: StorageHasher,
+        hash: Option<Hash>,
+    ) -> Result<BTreeMap<K1, BTreeMap<K2, V>>> {
+        let mut map = BTreeMap::<K1, BTreeMap<K2, V>>::new();
+        let mut iter =
+            self.iter_double_map::<K1, K2, V>(module, storage_name, hasher1, hasher2, hash);
+        while let Some(page) = iter.next_page().await? {
+            for (key1, key2, value) in page {
+                map.entry(key1).or_default().insert(key2, value);
+            }
+        }
+        Ok(map)
+    }
+
+    /// Returns the entries of the storage double map `module::storage_name` under `key1`.
+    ///
+    /// `hasher1` has to be a concat hasher, otherwise the keys can't be decoded.
+    pub async fn get_double_map_of<K1: Encode + Decode, K2: Decode + Ord, V: Decode>(
+        &self,
+        module: &str,
+        storage_name: &str,
+        hasher1: StorageHasher,
+        hasher2: StorageHasher,
+        key1: &K1,
+        hash: Option<Hash>,
+    ) -> Result<BTreeMap<K2, V>> {
+        let mut prefix = storage_prefix_for(module, storage_name);
+        prefix.extend(hasher1.hash(&key1.encode()));
+        let mut iter = StorageMapIter::<K1, K2, V> {
+            pairs: self.iter_pairs(StorageKey(prefix), hash),
+            hasher1,
+            hasher2,
+            _marker: PhantomData,
+        };
+        let mut map = BTreeMap::new();
+        while let Some(page) = iter.next_page().await? {
+            map.extend(page.into_iter().map(|(_, key2, value)| (key2, value)));
+        }
+        Ok(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use codec::Encode;
+
+    #[test]
+    fn test_decode_map_key() {
+        let account = AccountId::from([1u8; 32]);
+        let asset_id: AssetId = 1;
+
+        let mut key = storage_prefix_for("XAssets", "AssetBalance");
+        key.extend(StorageHasher::Blake2_128Concat.hash(&account.encode()));
+        key.extend(StorageHasher::Twox64Concat.hash(&asset_id.encode()));
+        let decoded: (AccountId, AssetId) = decode_map_key(
+            &key,
+            StorageHasher::Blake2_128Concat,
+            StorageHasher::Twox64Concat,
+        )
+        .unwrap();
+        assert_eq!(decoded, (account.clone(), asset_id));
+
+        let mut key = storage_prefix_for("System", "Account");
+        key.extend(StorageHasher::Blake2_128Concat.hash(&account.encode()));
+        let decoded: (AccountId, ()) = decode_map_key(
+            &key,
+            StorageHasher::Blake2_128Concat,
+            StorageHasher::Identity,
+        )
+        .unwrap();
+        assert_eq!(decoded, (account, ()));
+
+        // The key of the wrong hasher can't be decoded exactly.
+        assert!(decode_map_key::<AccountId, ()>(
+            &key,
+            StorageHasher::Twox64Concat,
+            StorageHasher::Identity
+        )
+        .is_err());
+    }
+}
@@ -1,16 +1,23 @@
 use super::*;
 
+use sp_runtime::ApplyExtrinsicResult;
+
+use super::storage::{decode_map_key, StorageHasher};
+
 impl Rpc {
     pub async fn get_accounts(&self, hash: Option<Hash>) -> Result<Vec<String>> {
         let prefix = storage_prefix_for("System", "Account");
-        let data = self.get_keys(StorageKey(prefix), hash).await?;
-
-        // System Account (32 bytes hex) + hash (16 bytes hex) = 48 bytes hex
-        let accounts = data
-            .into_iter()
-            .map(|x| hex::encode(x.0))
-            .map(|x| format!("0x{}", &x[STORAGE_PREFIX_LEN + BLAKE_HASH_LEN..]))
-            .collect::<Vec<_>>();
+        let keys = self.get_keys(StorageKey(prefix), hash).await?;
+
+        let mut accounts = Vec::with_capacity(keys.len());
+        for key in keys {
+            let (account, ()): (AccountId, ()) = decode_map_key(
+                &key.0,
+                StorageHasher::Blake2_128Concat,
+                StorageHasher::Identity,
+            )?;
+            accounts.push(format!("0x{}", hex::encode(account)));
+        }
         Ok(accounts)
     }
 
@@ -18,20 +25,41 @@ impl Rpc {
         &self,
         hash: Option<Hash>,
     ) -> Result<BTreeMap<AccountId, AccountInfo<ChainXRuntime>>> {
-        let prefix = storage_prefix_for("System", "Account");
-        let data = self.get_pairs(StorageKey(prefix), hash).await?;
+        self.get_map("System", "Account", StorageHasher::Blake2_128Concat, hash)
+            .await
+    }
 
-        let mut result = BTreeMap::new();
-        for (key, value) in data {
-            let pubkey = &hex::encode(&key.0)[STORAGE_PREFIX_LEN + BLAKE_HASH_LEN..];
-            let account_id = pubkey
-                .parse::<AccountId>()
-                .map_err(|err| anyhow!("{}", err))?;
+    /// Returns the `System::Account` of `who`, read with the hasher in `metadata`.
+    pub async fn get_account_info(
+        &self,
+        metadata: &Metadata,
+        who: &AccountId,
+        hash: Option<Hash>,
+    ) -> Result<AccountInfo<ChainXRuntime>> {
+        self.get_map_value(metadata, "System", "Account", who, hash)
+            .await?
+            .ok_or_else(|| anyhow!("Account info of {} not found", who))
+    }
 
-            let account_info: AccountInfo<ChainXRuntime> = Decode::decode(&mut value.0.as_slice())?;
+    /// Returns the next nonce of `who` by `system_accountNextIndex`, the extrinsics
+    /// pending in the transaction pool are counted in.
+    pub async fn account_next_index(&self, who: &AccountId) -> Result<Index> {
+        let params = Params::Array(vec![to_json_value(who)?]);
+        self.request("system_accountNextIndex", params).await
+    }
 
-            result.insert(account_id, account_info);
-        }
+    /// Dry runs the SCALE encoded extrinsic by `system_dryRun`, nothing is submitted.
+    pub async fn dry_run(
+        &self,
+        extrinsic: &[u8],
+        hash: Option<Hash>,
+    ) -> Result<ApplyExtrinsicResult> {
+        let params = Params::Array(vec![
+            to_json_value(Bytes(extrinsic.to_vec()))?,
+            to_json_value(hash)?,
+        ]);
+        let bytes: Bytes = self.request("system_dryRun", params).await?;
+        let result: ApplyExtrinsicResult = Decode::decode(&mut bytes.as_ref())?;
         Ok(result)
     }
 }
@@ -1,6 +1,27 @@
 use super::*;
 
-use crate::runtime::xpallets::xmining_asset::{AssetLedger, MinerLedger, MiningWeight};
+use serde::Deserialize;
+
+use super::storage::StorageHasher;
+use crate::{
+    runtime::xpallets::xmining_asset::{AssetLedger, MinerLedger, MiningWeight},
+    serde::serde_num_str,
+};
+
+/// Mining dividend of an asset miner, returned by `xminingasset_getDividendByAccount`.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MiningDividendInfo {
+    /// Dividend the miner receives on claiming.
+    #[serde(with = "serde_num_str")]
+    pub own: Balance,
+    /// Dividend paid to the referral of the miner on claiming.
+    #[serde(with = "serde_num_str")]
+    pub other: Balance,
+    /// Staked PCX still required to claim the dividend.
+    #[serde(with = "serde_num_str")]
+    pub insufficient_stake: Balance,
+}
 
 impl Rpc {
     pub async fn get_miner_ledgers(
@@ -8,56 +29,55 @@ impl Rpc {
         hash: Option<Hash>,
     ) -> Result<BTreeMap<AccountId, BTreeMap<AssetId, MinerLedger<MiningWeight, BlockNumber>>>>
     {
-        let prefix = storage_prefix_for("XMiningAsset", "MinerLedgers");
-        let data = self.get_pairs(StorageKey(prefix), hash).await?;
-
-        let mut miner_ledgers =
-            BTreeMap::<AccountId, BTreeMap<AssetId, MinerLedger<MiningWeight, BlockNumber>>>::new();
-        for (key, value) in data {
-            let key = hex::encode(&key.0);
-            let hashed_key1_key1_hashed_key2_key2 = &key[STORAGE_PREFIX_LEN..];
-
-            let hashed_key1_key1 = &hashed_key1_key1_hashed_key2_key2[..TWOX_HASH_LEN + 64];
-            let key1 = &hashed_key1_key1[TWOX_HASH_LEN..];
-            let account = key1
-                .parse::<AccountId>()
-                .map_err(|err| anyhow!("{}", err))?;
-
-            let hashed_key2_key2 = &hashed_key1_key1_hashed_key2_key2[hashed_key1_key1.len()..];
-            let key2 = &hashed_key2_key2[TWOX_HASH_LEN..];
-            let mut asset_id = [0u8; 4];
-            asset_id.copy_from_slice(hex::decode(key2)?.as_slice());
-
-            let miner_ledger: MinerLedger<MiningWeight, BlockNumber> =
-                Decode::decode(&mut value.0.as_slice())?;
-
-            let entry = miner_ledgers.entry(account).or_default();
-            entry.insert(AssetId::from_le_bytes(asset_id), miner_ledger);
-        }
-        Ok(miner_ledgers)
+        self.get_double_map(
+            "XMiningAsset",
+            "MinerLedgers",
+            StorageHasher::Twox64Concat,
+            StorageHasher::Twox64Concat,
+            hash,
+        )
+        .await
     }
 
     pub async fn get_asset_ledgers(
         &self,
         hash: Option<Hash>,
     ) -> Result<BTreeMap<AssetId, AssetLedger<MiningWeight, BlockNumber>>> {
-        let prefix = storage_prefix_for("XMiningAsset", "AssetLedgers");
-        let storage_key = StorageKey(prefix);
-        let data = self.get_pairs(storage_key, hash).await?;
-
-        let mut asset_ledgers = BTreeMap::new();
-        for (key, value) in data {
-            let key = hex::encode(&key.0);
-            let hashed_key_key = &key[STORAGE_PREFIX_LEN..];
-            let key = &hashed_key_key[TWOX_HASH_LEN..];
-            let mut asset_id = [0u8; 4];
-            asset_id.copy_from_slice(hex::decode(key)?.as_slice());
+        self.get_map(
+            "XMiningAsset",
+            "AssetLedgers",
+            StorageHasher::Twox64Concat,
+            hash,
+        )
+        .await
+    }
 
-            let asset_ledger: AssetLedger<MiningWeight, BlockNumber> =
-                Decode::decode(&mut value.0.as_slice())?;
+    /// Returns the miner ledgers of all the assets mined by `who`.
+    pub async fn get_miner_ledgers_of(
+        &self,
+        who: &AccountId,
+        hash: Option<Hash>,
+    ) -> Result<BTreeMap<AssetId, MinerLedger<MiningWeight, BlockNumber>>> {
+        self.get_double_map_of(
+            "XMiningAsset",
+            "MinerLedgers",
+            StorageHasher::Twox64Concat,
+            StorageHasher::Twox64Concat,
+            who,
+            hash,
+        )
+        .await
+    }
 
-            asset_ledgers.insert(AssetId::from_le_bytes(asset_id), asset_ledger);
-        }
-        Ok(asset_ledgers)
+    pub async fn get_mining_dividend(
+        &self,
+        who: AccountId,
+        hash: Option<Hash>,
+    ) -> Result<BTreeMap<AssetId, MiningDividendInfo>> {
+        let params = Params::Array(vec![to_json_value(who)?, to_json_value(hash)?]);
+        let data = self
+            .request("xminingasset_getDividendByAccount", params)
+            .await?;
+        Ok(data)
     }
 }
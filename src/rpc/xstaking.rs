@@ -1,7 +1,8 @@
 use super::*;
 
+use super::storage::StorageHasher;
 use crate::runtime::xpallets::xstaking::{
-    NominatorLedger, Unbonded, ValidatorLedger, ValidatorProfile, VoteWeight,
+    LockedType, NominatorLedger, Unbonded, ValidatorLedger, ValidatorProfile, VoteWeight,
 };
 
 impl Rpc {
@@ -19,21 +20,8 @@ impl Rpc {
         &self,
         hash: Option<Hash>,
     ) -> Result<BTreeMap<AccountId, ValidatorProfile<BlockNumber>>> {
-        let prefix = storage_prefix_for("XStaking", "Validators");
-        let data = self.get_pairs(StorageKey(prefix), hash).await?;
-        let mut validator_profiles = BTreeMap::new();
-        for (key, value) in data {
-            let key = hex::encode(&key.0);
-            let hashed_key_key = &key[STORAGE_PREFIX_LEN..];
-            let key = &hashed_key_key[TWOX_HASH_LEN..];
-            let validator = key.parse::<AccountId>().map_err(|err| anyhow!("{}", err))?;
-
-            let validator_profile: ValidatorProfile<BlockNumber> =
-                Decode::decode(&mut value.0.as_slice())?;
-
-            validator_profiles.insert(validator, validator_profile);
-        }
-        Ok(validator_profiles)
+        self.get_map("XStaking", "Validators", StorageHasher::Twox64Concat, hash)
+            .await
     }
 
     pub async fn get_nominations(
@@ -42,56 +30,40 @@ impl Rpc {
     ) -> Result<
         BTreeMap<AccountId, BTreeMap<AccountId, NominatorLedger<Balance, VoteWeight, BlockNumber>>>,
     > {
-        let prefix = storage_prefix_for("XStaking", "Nominations");
-        let data = self.get_pairs(StorageKey(prefix), hash).await?;
-        let mut nominations = BTreeMap::<
-            AccountId,
-            BTreeMap<AccountId, NominatorLedger<Balance, VoteWeight, BlockNumber>>,
-        >::new();
-        for (key, value) in data {
-            let key = hex::encode(&key.0);
-            let hashed_key1_key1_hashed_key2_key2 = &key[STORAGE_PREFIX_LEN..];
-
-            let hashed_key1_key1 = &hashed_key1_key1_hashed_key2_key2[..TWOX_HASH_LEN + 64];
-            let key1 = &hashed_key1_key1[TWOX_HASH_LEN..];
-            let nominator = key1
-                .parse::<AccountId>()
-                .map_err(|err| anyhow!("{}", err))?;
-
-            let hashed_key2_key2 = &hashed_key1_key1_hashed_key2_key2[hashed_key1_key1.len()..];
-            let key2 = &hashed_key2_key2[TWOX_HASH_LEN..];
-            let nominee = key2
-                .parse::<AccountId>()
-                .map_err(|err| anyhow!("{}", err))?;
-
-            let nominator_ledger: NominatorLedger<Balance, VoteWeight, BlockNumber> =
-                Decode::decode(&mut value.0.as_slice())?;
-
-            let entry = nominations.entry(nominator).or_default();
-            entry.insert(nominee, nominator_ledger);
-        }
-        Ok(nominations)
+        self.get_double_map(
+            "XStaking",
+            "Nominations",
+            StorageHasher::Twox64Concat,
+            StorageHasher::Twox64Concat,
+            hash,
+        )
+        .await
     }
 
     pub async fn get_validator_ledgers(
         &self,
         hash: Option<Hash>,
     ) -> Result<BTreeMap<AccountId, ValidatorLedger<Balance, VoteWeight, BlockNumber>>> {
-        let prefix = storage_prefix_for("XStaking", "ValidatorLedgers");
-        let data = self.get_pairs(StorageKey(prefix), hash).await?;
-        let mut validator_ledgers = BTreeMap::new();
-        for (key, value) in data {
-            let key = hex::encode(&key.0);
-            let hashed_key_key = &key[STORAGE_PREFIX_LEN..];
-            let key = &hashed_key_key[TWOX_HASH_LEN..];
-            let validator = key.parse::<AccountId>().map_err(|err| anyhow!("{}", err))?;
-
-            let validator_ledger: ValidatorLedger<Balance, VoteWeight, BlockNumber> =
-                Decode::decode(&mut value.0.as_slice())?;
+        self.get_map(
+            "XStaking",
+            "ValidatorLedgers",
+            StorageHasher::Twox64Concat,
+            hash,
+        )
+        .await
+    }
 
-            validator_ledgers.insert(validator, validator_ledger);
-        }
-        Ok(validator_ledgers)
+    /// Returns the `XStaking::Locks` of `who`, read with the hasher in `metadata`.
+    pub async fn get_locks(
+        &self,
+        metadata: &Metadata,
+        who: &AccountId,
+        hash: Option<Hash>,
+    ) -> Result<BTreeMap<LockedType, Balance>> {
+        Ok(self
+            .get_map_value(metadata, "XStaking", "Locks", who, hash)
+            .await?
+            .unwrap_or_default())
     }
 
     pub async fn get_staking_dividend(
@@ -101,7 +73,6 @@ impl Rpc {
     ) -> Result<BTreeMap<AccountId, Balance>> {
         let params = Params::Array(vec![to_json_value(who)?, to_json_value(hash)?]);
         let data: BTreeMap<AccountId, String> = self
-            .client
             .request("xstaking_getDividendByAccount", params)
             .await?;
         Ok(data
@@ -123,7 +94,6 @@ impl Rpc {
     ) -> Result<BTreeMap<AccountId, NominatorLedger<Balance, Balance, BlockNumber>>> {
         let params = Params::Array(vec![to_json_value(who)?, to_json_value(hash)?]);
         let data: BTreeMap<AccountId, NominatorLedger<String, String, BlockNumber>> = self
-            .client
             .request("xstaking_getNominationByAccount", params)
             .await?;
 
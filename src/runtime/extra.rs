@@ -0,0 +1,123 @@
+//! Signed extensions of the ChainX transactions.
+
+use std::marker::PhantomData;
+
+use codec::{Decode, Encode};
+use sp_runtime::{
+    generic::Era, traits::SignedExtension, transaction_validity::TransactionValidityError,
+};
+use subxt::extrinsic::{
+    ChargeTransactionPayment, CheckEra, CheckGenesis, CheckNonce, CheckSpecVersion, CheckTxVersion,
+    CheckWeight, SignedExtra,
+};
+
+use super::{
+    primitives::{AccountId, Balance, Hash, Index},
+    ChainXRuntime,
+};
+
+/// The same extensions as subxt's `DefaultExtra`, with the transaction era and tip configurable.
+///
+/// `SignedExtra::new` gives an immortal transaction without tip as `DefaultExtra` does.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, Debug)]
+pub struct ChainXExtra {
+    spec_version: u32,
+    tx_version: u32,
+    nonce: Index,
+    genesis_hash: Hash,
+    era: Era,
+    /// Hash of the block the era starts at, the genesis hash for an immortal transaction.
+    era_hash: Hash,
+    tip: Balance,
+}
+
+impl ChainXExtra {
+    /// Sets the transaction era, `era_hash` is the hash of the block at `era.birth()`.
+    pub fn era(mut self, era: Era, era_hash: Hash) -> Self {
+        self.era = era;
+        self.era_hash = era_hash;
+        self
+    }
+
+    /// Sets the tip paid to the block author on top of the fee.
+    pub fn tip(mut self, tip: Balance) -> Self {
+        self.tip = tip;
+        self
+    }
+}
+
+impl SignedExtra<ChainXRuntime> for ChainXExtra {
+    type Extra = (
+        CheckSpecVersion<ChainXRuntime>,
+        CheckTxVersion<ChainXRuntime>,
+        CheckGenesis<ChainXRuntime>,
+        CheckEra<ChainXRuntime>,
+        CheckNonce<ChainXRuntime>,
+        CheckWeight<ChainXRuntime>,
+        ChargeTransactionPayment<ChainXRuntime>,
+    );
+
+    fn new(spec_version: u32, tx_version: u32, nonce: Index, genesis_hash: Hash) -> Self {
+        Self {
+            spec_version,
+            tx_version,
+            nonce,
+            genesis_hash,
+            era: Era::Immortal,
+            era_hash: genesis_hash,
+            tip: 0,
+        }
+    }
+
+    fn extra(&self) -> Self::Extra {
+        (
+            CheckSpecVersion(PhantomData, self.spec_version),
+            CheckTxVersion(PhantomData, self.tx_version),
+            CheckGenesis(PhantomData, self.genesis_hash),
+            CheckEra((self.era, PhantomData), self.era_hash),
+            CheckNonce(self.nonce),
+            CheckWeight(PhantomData),
+            ChargeTransactionPayment(self.tip),
+        )
+    }
+}
+
+impl SignedExtension for ChainXExtra {
+    const IDENTIFIER: &'static str = "ChainXExtra";
+    type AccountId = AccountId;
+    type Call = ();
+    type AdditionalSigned =
+        <<Self as SignedExtra<ChainXRuntime>>::Extra as SignedExtension>::AdditionalSigned;
+    type Pre = ();
+
+    fn additional_signed(&self) -> Result<Self::AdditionalSigned, TransactionValidityError> {
+        self.extra().additional_signed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use subxt::extrinsic::DefaultExtra;
+
+    #[test]
+    fn test_default_extra_compatible() {
+        let genesis_hash = Hash::repeat_byte(1);
+        let extra = ChainXExtra::new(1, 2, 3, genesis_hash);
+        let default = DefaultExtra::<ChainXRuntime>::new(1, 2, 3, genesis_hash);
+        assert_eq!(extra.extra().encode(), default.extra().encode());
+        assert_eq!(
+            extra.additional_signed().unwrap().encode(),
+            default.additional_signed().unwrap().encode()
+        );
+
+        let era = Era::mortal(64, 100);
+        let era_hash = Hash::repeat_byte(2);
+        let mortal = extra.era(era, era_hash).tip(5);
+        assert_ne!(mortal.extra().encode(), default.extra().encode());
+        let (_, _, genesis, era_hash_signed, ..) = mortal.additional_signed().unwrap();
+        assert_eq!(genesis, genesis_hash);
+        assert_eq!(era_hash_signed, era_hash);
+    }
+}
@@ -1,25 +1,41 @@
+pub mod extra;
 pub mod primitives;
 pub mod xpallets;
 
+use anyhow::{anyhow, Result};
 use pallet_im_online::sr25519::AuthorityId as ImOnlineId;
+use serde::Serialize;
 use sp_authority_discovery::AuthorityId as AuthorityDiscoveryId;
 use sp_consensus_babe::AuthorityId as BabeId;
-use sp_core::sr25519;
+use sp_core::{ecdsa, ed25519, sr25519, Pair};
 use sp_finality_grandpa::AuthorityId as GrandpaId;
 use sp_runtime::{generic::Header, impl_opaque_keys, OpaqueExtrinsic};
+use structopt::clap::arg_enum;
 use subxt::{
-    balances::{AccountData, Balances},
-    extrinsic::DefaultExtra,
-    sudo::Sudo,
-    system::System,
-    Client, PairSigner, Runtime,
+    balances::{AccountData, Balances, BalancesEventsDecoder},
+    extrinsic::{SignedPayload, UncheckedExtrinsic},
+    sudo::{Sudo, SudoEventsDecoder},
+    system::{System, SystemEventsDecoder},
+    Client, EventsDecoder, PairSigner, Runtime, Signer,
 };
 
-use crate::frame::session::Session;
+use crate::{
+    frame::{
+        self,
+        session::{Session, SessionEventsDecoder},
+        utility::{Utility, UtilityEventsDecoder},
+    },
+    metadata::compat::PalletDecl,
+};
 
 use self::{
+    extra::ChainXExtra,
     primitives::*,
-    xpallets::{xassets::XAssets, xmining_asset::XMiningAsset, xstaking::XStaking},
+    xpallets::{
+        xassets::{XAssets, XAssetsEventsDecoder},
+        xmining_asset::{XMiningAsset, XMiningAssetEventsDecoder},
+        xstaking::{XStaking, XStakingEventsDecoder},
+    },
 };
 
 /// Concrete type definitions for ChainX.
@@ -28,7 +44,7 @@ pub struct ChainXRuntime;
 
 impl Runtime for ChainXRuntime {
     type Signature = Signature;
-    type Extra = DefaultExtra<Self>;
+    type Extra = ChainXExtra;
 }
 
 impl System for ChainXRuntime {
@@ -77,6 +93,9 @@ impl sp_runtime::BoundToRuntimeAppPublic for AuthorityDiscovery {
     type Public = AuthorityDiscoveryId;
 }
 
+/// Size of each public key in the session keys.
+pub const SESSION_KEY_LEN: usize = 32;
+
 impl_opaque_keys! {
     /// Substrate base runtime keys
     pub struct BasicSessionKeys {
@@ -96,15 +115,131 @@ impl Session for ChainXRuntime {
     type Keys = BasicSessionKeys;
 }
 
+impl Utility for ChainXRuntime {}
+
 impl XAssets for ChainXRuntime {}
 impl XMiningAsset for ChainXRuntime {}
 impl XStaking for ChainXRuntime {}
 
+/// The pallets whose calls and storages are hard-coded, see `metadata::compat`.
+pub const PALLETS: &[PalletDecl] = &[
+    frame::session::PALLET,
+    xpallets::xassets::PALLET,
+    xpallets::xmining_asset::PALLET,
+    xpallets::xstaking::PALLET,
+];
+
 /// ChainX `Client` for ChainX runtime.
 pub type ChainXClient = Client<ChainXRuntime>;
 
 /// ChainX `Pair` for ChainX runtime.
 pub type ChainXPair = sr25519::Pair;
 
-/// ChainX `PairSigner` for ChainX runtime.
-pub type ChainXSigner = PairSigner<ChainXRuntime, ChainXPair>;
+arg_enum! {
+  /// Signature schemes supported by `MultiSignature`.
+  #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+  #[serde(rename_all = "lowercase")]
+  pub enum CryptoScheme {
+      Sr25519,
+      Ed25519,
+      Ecdsa,
+  }
+}
+
+impl Default for CryptoScheme {
+    fn default() -> Self {
+        Self::Sr25519
+    }
+}
+
+/// ChainX `PairSigner` for ChainX runtime, of any signature scheme.
+pub enum ChainXSigner {
+    Sr25519(PairSigner<ChainXRuntime, sr25519::Pair>),
+    Ed25519(PairSigner<ChainXRuntime, ed25519::Pair>),
+    Ecdsa(PairSigner<ChainXRuntime, ecdsa::Pair>),
+}
+
+impl From<sr25519::Pair> for ChainXSigner {
+    fn from(pair: sr25519::Pair) -> Self {
+        Self::Sr25519(PairSigner::new(pair))
+    }
+}
+
+impl From<ed25519::Pair> for ChainXSigner {
+    fn from(pair: ed25519::Pair) -> Self {
+        Self::Ed25519(PairSigner::new(pair))
+    }
+}
+
+impl From<ecdsa::Pair> for ChainXSigner {
+    fn from(pair: ecdsa::Pair) -> Self {
+        Self::Ecdsa(PairSigner::new(pair))
+    }
+}
+
+impl ChainXSigner {
+    /// Creates the signer of `scheme` from a secret URI, e.g., `<mnemonic>//hard/soft///password`.
+    pub fn from_uri(scheme: CryptoScheme, uri: &str) -> Result<Self> {
+        let err = |err| anyhow!("Failed to generate {} Pair from uri: {:?}", scheme, err);
+        Ok(match scheme {
+            CryptoScheme::Sr25519 => sr25519::Pair::from_string(uri, None).map_err(err)?.into(),
+            CryptoScheme::Ed25519 => ed25519::Pair::from_string(uri, None).map_err(err)?.into(),
+            CryptoScheme::Ecdsa => ecdsa::Pair::from_string(uri, None).map_err(err)?.into(),
+        })
+    }
+
+    pub fn scheme(&self) -> CryptoScheme {
+        match self {
+            Self::Sr25519(_) => CryptoScheme::Sr25519,
+            Self::Ed25519(_) => CryptoScheme::Ed25519,
+            Self::Ecdsa(_) => CryptoScheme::Ecdsa,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Signer<ChainXRuntime> for ChainXSigner {
+    fn account_id(&self) -> &AccountId {
+        match self {
+            Self::Sr25519(signer) => signer.account_id(),
+            Self::Ed25519(signer) => signer.account_id(),
+            Self::Ecdsa(signer) => signer.account_id(),
+        }
+    }
+
+    fn nonce(&self) -> Option<Index> {
+        match self {
+            Self::Sr25519(signer) => signer.nonce(),
+            Self::Ed25519(signer) => signer.nonce(),
+            Self::Ecdsa(signer) => signer.nonce(),
+        }
+    }
+
+    async fn sign(
+        &self,
+        extrinsic: SignedPayload<ChainXRuntime>,
+    ) -> Result<UncheckedExtrinsic<ChainXRuntime>, String> {
+        match self {
+            Self::Sr25519(signer) => signer.sign(extrinsic).await,
+            Self::Ed25519(signer) => signer.sign(extrinsic).await,
+            Self::Ecdsa(signer) => signer.sign(extrinsic).await,
+        }
+    }
+}
+
+/// Returns an `EventsDecoder` that is able to decode the events of all the known modules.
+///
+/// Used when the call of the extrinsic is not known statically, e.g., submitting a
+/// pre-signed extrinsic.
+pub fn events_decoder(client: &ChainXClient) -> EventsDecoder<ChainXRuntime> {
+    let mut decoder = EventsDecoder::<ChainXRuntime>::new(client.metadata().clone());
+    decoder.with_system();
+    decoder.with_balances();
+    decoder.with_sudo();
+    decoder.with_session();
+    decoder.with_utility();
+    decoder.with_x_assets();
+    decoder.with_x_mining_asset();
+    decoder.with_x_staking();
+    decoder
+}
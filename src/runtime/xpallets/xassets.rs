@@ -1,6 +1,7 @@
 use std::{collections::BTreeMap, marker::PhantomData};
 
 use codec::{Decode, Encode};
+use serde::Serialize;
 use subxt::{
     balances::{Balances, BalancesEventsDecoder},
     module,
@@ -8,7 +9,11 @@ use subxt::{
     Call, Event, Store,
 };
 
-use crate::runtime::primitives::AssetId;
+use crate::{
+    metadata::compat::{CallDecl, PalletDecl, StorageDecl},
+    rpc::storage::StorageHasher::{Blake2_128Concat, Twox64Concat},
+    runtime::primitives::AssetId,
+};
 
 #[module]
 pub trait XAssets: Balances + System {}
@@ -65,9 +70,37 @@ pub struct TotalAssetBalanceStore<T: XAssets> {
     pub asset_id: AssetId,
 }
 
+/// The calls and storages above, checked against the runtime metadata.
+pub const PALLET: PalletDecl = PalletDecl {
+    name: "XAssets",
+    calls: &[CallDecl {
+        name: "transfer",
+        args: &[
+            ("dest", "<T::Lookup as StaticLookup>::Source"),
+            ("asset_id", "Compact<AssetId>"),
+            ("value", "Compact<BalanceOf<T>>"),
+        ],
+    }],
+    storage: &[
+        StorageDecl {
+            name: "AssetBalance",
+            keys: &[
+                (Some(Blake2_128Concat), "T::AccountId"),
+                (Some(Twox64Concat), "AssetId"),
+            ],
+            value: "BTreeMap<AssetType, BalanceOf<T>>",
+        },
+        StorageDecl {
+            name: "TotalAssetBalance",
+            keys: &[(Some(Twox64Concat), "AssetId")],
+            value: "BTreeMap<AssetType, BalanceOf<T>>",
+        },
+    ],
+};
+
 pub type BalanceOf<T> = <T as Balances>::Balance;
 
-#[derive(Clone, Copy, Debug, Ord, PartialOrd, Eq, PartialEq, Encode, Decode)]
+#[derive(Clone, Copy, Debug, Ord, PartialOrd, Eq, PartialEq, Encode, Decode, Serialize)]
 pub enum AssetType {
     Usable,
     Locked,
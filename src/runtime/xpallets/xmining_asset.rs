@@ -8,7 +8,11 @@ use subxt::{
     Call, Event, Store,
 };
 
-use crate::runtime::primitives::AssetId;
+use crate::{
+    metadata::compat::{CallDecl, PalletDecl, StorageDecl},
+    rpc::storage::StorageHasher::Twox64Concat,
+    runtime::primitives::AssetId,
+};
 
 #[module]
 pub trait XMiningAsset: Balances + System {}
@@ -60,6 +64,30 @@ pub struct MinerLedgersStore<'a, T: XMiningAsset> {
     pub asset_id: AssetId,
 }
 
+/// The calls and storages above, checked against the runtime metadata.
+pub const PALLET: PalletDecl = PalletDecl {
+    name: "XMiningAsset",
+    calls: &[CallDecl {
+        name: "claim",
+        args: &[("target", "AssetId")],
+    }],
+    storage: &[
+        StorageDecl {
+            name: "AssetLedgers",
+            keys: &[(Some(Twox64Concat), "AssetId")],
+            value: "AssetLedger<MiningWeight, T::BlockNumber>",
+        },
+        StorageDecl {
+            name: "MinerLedgers",
+            keys: &[
+                (Some(Twox64Concat), "T::AccountId"),
+                (Some(Twox64Concat), "AssetId"),
+            ],
+            value: "MinerLedger<MiningWeight, T::BlockNumber>",
+        },
+    ],
+};
+
 pub type MiningWeight = u128;
 
 /// Vote weight properties of validator.
@@ -10,7 +10,12 @@ use subxt::{
     balances::{Balances, BalancesEventsDecoder},
     module,
     system::{System, SystemEventsDecoder},
-    Call, Store,
+    Call, Event, Store,
+};
+
+use crate::{
+    metadata::compat::{CallDecl, PalletDecl, StorageDecl},
+    rpc::storage::StorageHasher::Twox64Concat,
 };
 
 #[module]
@@ -67,6 +72,15 @@ pub struct ClaimCall<'a, T: XStaking> {
     pub target: &'a <T as System>::Address,
 }
 
+#[derive(Clone, Debug, PartialEq, Call, Encode)]
+pub struct UnlockUnbondedWithdrawalCall<'a, T: XStaking> {
+    /// Target of the unbonded chunk.
+    pub target: &'a <T as System>::Address,
+    /// Index of the unbonded chunk of the target.
+    #[codec(compact)]
+    pub unbonded_index: UnbondedIndex,
+}
+
 #[derive(Clone, Debug, PartialEq, Call, Encode)]
 pub struct ValidateCall<T: XStaking> {
     /// Runtime marker.
@@ -100,6 +114,82 @@ pub struct SetSessionsPerEraCall<T: XStaking> {
 /// Simple index type with which we can count sessions.
 pub type SessionIndex = u32;
 
+/// Index of the unbonded chunks of a nominator.
+pub type UnbondedIndex = u32;
+
+// ============================================================================
+// Event
+// ============================================================================
+
+/// A validator was registered. [validator]
+#[derive(Clone, Debug, PartialEq, Event, Decode)]
+pub struct RegisteredEvent<T: XStaking> {
+    /// The registered validator.
+    pub validator: <T as System>::AccountId,
+}
+
+/// A validator was chilled. [validator]
+#[derive(Clone, Debug, PartialEq, Event, Decode)]
+pub struct ChilledEvent<T: XStaking> {
+    /// The chilled validator.
+    pub validator: <T as System>::AccountId,
+}
+
+/// A nominator bonded to the validator this amount. [nominator, validator, amount]
+#[derive(Clone, Debug, PartialEq, Event, Decode)]
+pub struct BondedEvent<T: XStaking> {
+    /// Nominator of the bond.
+    pub nominator: <T as System>::AccountId,
+    /// Validator of the bond.
+    pub validator: <T as System>::AccountId,
+    /// Amount of the bond.
+    pub amount: <T as Balances>::Balance,
+}
+
+/// A nominator switched the vote from one validator to another. [nominator, from, to, amount]
+#[derive(Clone, Debug, PartialEq, Event, Decode)]
+pub struct RebondedEvent<T: XStaking> {
+    /// Nominator of the rebond.
+    pub nominator: <T as System>::AccountId,
+    /// Source of the rebond.
+    pub from: <T as System>::AccountId,
+    /// Target of the rebond.
+    pub to: <T as System>::AccountId,
+    /// Amount of the rebond.
+    pub amount: <T as Balances>::Balance,
+}
+
+/// A nominator unbonded this amount. [nominator, validator, amount]
+#[derive(Clone, Debug, PartialEq, Event, Decode)]
+pub struct UnbondedEvent<T: XStaking> {
+    /// Nominator of the unbond.
+    pub nominator: <T as System>::AccountId,
+    /// Validator of the unbond.
+    pub validator: <T as System>::AccountId,
+    /// Amount of the unbond.
+    pub amount: <T as Balances>::Balance,
+}
+
+/// A nominator claimed the staking dividend. [nominator, validator, dividend]
+#[derive(Clone, Debug, PartialEq, Event, Decode)]
+pub struct ClaimedEvent<T: XStaking> {
+    /// Nominator who claimed the dividend.
+    pub nominator: <T as System>::AccountId,
+    /// Validator the dividend comes from.
+    pub validator: <T as System>::AccountId,
+    /// Amount of the dividend.
+    pub dividend: <T as Balances>::Balance,
+}
+
+/// The nominator withdrew the locked balance from the unlocking queue. [nominator, amount]
+#[derive(Clone, Debug, PartialEq, Event, Decode)]
+pub struct WithdrawnEvent<T: XStaking> {
+    /// Nominator of the withdrawal.
+    pub nominator: <T as System>::AccountId,
+    /// Amount of the withdrawal.
+    pub amount: <T as Balances>::Balance,
+}
+
 // ============================================================================
 // Storage
 // ============================================================================
@@ -132,6 +222,99 @@ pub struct LocksStore<'a, T: XStaking> {
     pub staker: &'a T::AccountId,
 }
 
+/// The calls and storages above, checked against the runtime metadata.
+pub const PALLET: PalletDecl = PalletDecl {
+    name: "XStaking",
+    calls: &[
+        CallDecl {
+            name: "register",
+            args: &[
+                ("validator_nickname", "ReferralId"),
+                ("initial_bond", "Compact<BalanceOf<T>>"),
+            ],
+        },
+        CallDecl {
+            name: "bond",
+            args: &[
+                ("target", "<T::Lookup as StaticLookup>::Source"),
+                ("value", "Compact<BalanceOf<T>>"),
+            ],
+        },
+        CallDecl {
+            name: "rebond",
+            args: &[
+                ("from", "<T::Lookup as StaticLookup>::Source"),
+                ("to", "<T::Lookup as StaticLookup>::Source"),
+                ("value", "Compact<BalanceOf<T>>"),
+            ],
+        },
+        CallDecl {
+            name: "unbond",
+            args: &[
+                ("target", "<T::Lookup as StaticLookup>::Source"),
+                ("value", "Compact<BalanceOf<T>>"),
+            ],
+        },
+        CallDecl {
+            name: "unlock_unbonded_withdrawal",
+            args: &[
+                ("target", "<T::Lookup as StaticLookup>::Source"),
+                ("unbonded_index", "Compact<UnbondedIndex>"),
+            ],
+        },
+        CallDecl {
+            name: "claim",
+            args: &[("target", "<T::Lookup as StaticLookup>::Source")],
+        },
+        CallDecl {
+            name: "validate",
+            args: &[],
+        },
+        CallDecl {
+            name: "chill",
+            args: &[],
+        },
+        CallDecl {
+            name: "set_validator_count",
+            args: &[("new", "Compact<u32>")],
+        },
+        CallDecl {
+            name: "set_sessions_per_era",
+            args: &[("new", "Compact<SessionIndex>")],
+        },
+    ],
+    storage: &[
+        StorageDecl {
+            name: "VestingAccount",
+            keys: &[],
+            value: "T::AccountId",
+        },
+        StorageDecl {
+            name: "Validators",
+            keys: &[(Some(Twox64Concat), "T::AccountId")],
+            value: "ValidatorProfile<T::BlockNumber>",
+        },
+        StorageDecl {
+            name: "ValidatorLedgers",
+            keys: &[(Some(Twox64Concat), "T::AccountId")],
+            value: "ValidatorLedger<BalanceOf<T>, VoteWeight, T::BlockNumber>",
+        },
+        StorageDecl {
+            name: "Nominations",
+            keys: &[
+                (Some(Twox64Concat), "T::AccountId"),
+                (Some(Twox64Concat), "T::AccountId"),
+            ],
+            value: "NominatorLedger<BalanceOf<T>, VoteWeight, T::BlockNumber>",
+        },
+        StorageDecl {
+            name: "Locks",
+            keys: &[(None, "T::AccountId")],
+            value: "BTreeMap<LockedType, BalanceOf<T>>",
+        },
+    ],
+};
+
 pub type BalanceOf<T> = <T as Balances>::Balance;
 
 pub type VoteWeight = u128;
@@ -200,7 +383,7 @@ pub struct Unbonded<Balance, BlockNumber> {
 }
 
 /// Detailed types of reserved balances in Staking.
-#[derive(PartialEq, PartialOrd, Ord, Eq, Clone, Copy, Encode, Decode, Debug)]
+#[derive(PartialEq, PartialOrd, Ord, Eq, Clone, Copy, Encode, Decode, Debug, Serialize)]
 pub enum LockedType {
     /// Locked balances when nominator calls `bond`.
     Bonded,
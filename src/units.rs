@@ -0,0 +1,179 @@
+//! Balance units of the ChainX assets.
+//!
+//! An amount can be given in the raw units, e.g., `150000000`, or in the asset units
+//! with a decimal point or the asset symbol, e.g., `1.5` and `1.5PCX`.
+
+use anyhow::{anyhow, Result};
+
+use crate::runtime::primitives::{AssetId, Balance};
+
+/// Unit of an asset.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Unit {
+    pub symbol: &'static str,
+    pub decimals: u32,
+}
+
+/// Asset id of PCX.
+pub const PCX_ASSET_ID: AssetId = 0;
+/// Asset id of X-BTC.
+pub const X_BTC_ASSET_ID: AssetId = 1;
+
+pub const PCX: Unit = Unit {
+    symbol: "PCX",
+    decimals: 8,
+};
+
+pub const X_BTC: Unit = Unit {
+    symbol: "X-BTC",
+    decimals: 8,
+};
+
+impl Unit {
+    /// Returns the unit of the known asset.
+    pub fn of_asset(asset_id: AssetId) -> Option<Self> {
+        match asset_id {
+            PCX_ASSET_ID => Some(PCX),
+            X_BTC_ASSET_ID => Some(X_BTC),
+            _ => None,
+        }
+    }
+
+    /// Returns the raw units of one asset unit.
+    pub fn one(&self) -> Balance {
+        10u128.pow(self.decimals)
+    }
+
+    /// Parses `150000000` as the raw units, `1.5` and `1.5PCX` as the asset units.
+    pub fn parse(&self, amount: &str) -> Result<Balance> {
+        let amount = amount.trim().replace('_', "");
+        let number = amount.trim_end_matches(|c: char| c.is_ascii_alphabetic() || c == '-');
+        let symbol = amount[number.len()..].trim_start();
+        let number = number.trim();
+
+        if !symbol.is_empty() && !symbol.eq_ignore_ascii_case(self.symbol) {
+            return Err(anyhow!(
+                "Invalid unit {} of amount {}, expected {}",
+                symbol,
+                amount,
+                self.symbol
+            ));
+        }
+
+        if symbol.is_empty() && !number.contains('.') {
+            return number
+                .parse()
+                .map_err(|err| anyhow!("Invalid amount {}: {}", amount, err));
+        }
+
+        let (integer, fraction) = match number.find('.') {
+            Some(pos) => (&number[..pos], &number[pos + 1..]),
+            None => (number, ""),
+        };
+        if fraction.len() > self.decimals as usize {
+            return Err(anyhow!(
+                "Invalid amount {}: {} has at most {} decimals",
+                amount,
+                self.symbol,
+                self.decimals
+            ));
+        }
+        let is_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+        if (integer.is_empty() && fraction.is_empty())
+            || !is_digits(integer)
+            || !is_digits(fraction)
+        {
+            return Err(anyhow!("Invalid amount {}", amount));
+        }
+
+        let integer = if integer.is_empty() {
+            0
+        } else {
+            integer.parse::<Balance>()?
+        };
+        let fraction = if fraction.is_empty() {
+            0
+        } else {
+            fraction.parse::<Balance>()? * 10u128.pow(self.decimals - fraction.len() as u32)
+        };
+        integer
+            .checked_mul(self.one())
+            .and_then(|integer| integer.checked_add(fraction))
+            .ok_or_else(|| anyhow!("Amount {} overflows", amount))
+    }
+
+    /// Formats the raw units, e.g., `150000000` => `1.5 PCX`.
+    pub fn format(&self, value: Balance) -> String {
+        let integer = value / self.one();
+        let fraction = value % self.one();
+        if fraction == 0 {
+            format!("{} {}", integer, self.symbol)
+        } else {
+            let fraction = format!("{:0width$}", fraction, width = self.decimals as usize);
+            format!(
+                "{}.{} {}",
+                integer,
+                fraction.trim_end_matches('0'),
+                self.symbol
+            )
+        }
+    }
+}
+
+/// Parses the amount of PCX, used as the parser of command line arguments.
+pub fn parse_pcx(amount: &str) -> Result<Balance> {
+    PCX.parse(amount)
+}
+
+/// Parses the amount of asset `asset_id`, only the raw units are accepted for the unknown assets.
+pub fn parse_asset_amount(asset_id: AssetId, amount: &str) -> Result<Balance> {
+    match Unit::of_asset(asset_id) {
+        Some(unit) => unit.parse(amount),
+        None => amount
+            .trim()
+            .replace('_', "")
+            .parse()
+            .map_err(|err| anyhow!("Invalid amount {} of asset {}: {}", amount, asset_id, err)),
+    }
+}
+
+/// Formats the amount of asset `asset_id`, the unknown assets are formatted in the raw units.
+pub fn format_asset_amount(asset_id: AssetId, value: Balance) -> String {
+    match Unit::of_asset(asset_id) {
+        Some(unit) => unit.format(value),
+        None => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(PCX.parse("150000000").unwrap(), 150_000_000);
+        assert_eq!(PCX.parse("1.5").unwrap(), 150_000_000);
+        assert_eq!(PCX.parse("1.5PCX").unwrap(), 150_000_000);
+        assert_eq!(PCX.parse("1.5 pcx").unwrap(), 150_000_000);
+        assert_eq!(PCX.parse("2PCX").unwrap(), 200_000_000);
+        assert_eq!(PCX.parse(".00000001").unwrap(), 1);
+        assert_eq!(PCX.parse("1_000.5").unwrap(), 100_050_000_000);
+        assert_eq!(X_BTC.parse("0.1X-BTC").unwrap(), 10_000_000);
+        assert!(PCX.parse("1.5BTC").is_err());
+        assert!(PCX.parse("0.000000001").is_err());
+        assert!(PCX.parse("1.2.3").is_err());
+        assert!(PCX.parse("PCX").is_err());
+        assert!(PCX.parse("-1").is_err());
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(PCX.format(150_000_000), "1.5 PCX");
+        assert_eq!(PCX.format(100_000_000), "1 PCX");
+        // The leading zeros of the fractional part must be kept.
+        assert_eq!(PCX.format(100_000_001), "1.00000001 PCX");
+        assert_eq!(PCX.format(5_000_000), "0.05 PCX");
+        assert_eq!(PCX.format(0), "0 PCX");
+        assert_eq!(format_asset_amount(100, 42), "42");
+    }
+}
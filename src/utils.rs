@@ -1,14 +1,23 @@
-use std::{fs::File, io::Read, path::Path};
+use std::{fs::File, io::Read, path::Path, str::FromStr};
 
 use anyhow::{anyhow, Result};
-use sp_core::crypto::{Pair, Public, Ss58Codec};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use sp_core::{
+    crypto::{Pair, Public, Ss58Codec},
+    storage::StorageKey,
+};
 use sp_keyring::AccountKeyring;
 use sp_runtime::traits::{IdentifyAccount, Verify};
+use structopt::{clap::arg_enum, StructOpt};
 use subxt::ClientBuilder;
 
-use crate::runtime::{
-    primitives::{AccountId, BlockNumber, Hash, Signature},
-    ChainXClient, ChainXRuntime,
+use crate::{
+    metadata::compat::{self, CompatCheck},
+    rpc::{storage_prefix_for, Rpc},
+    runtime::{
+        primitives::{AccountId, BlockNumber, Hash, Signature},
+        ChainXClient, ChainXRuntime,
+    },
 };
 
 pub fn read_code<P: AsRef<Path>>(code_path: P) -> Result<Vec<u8>> {
@@ -63,10 +72,26 @@ pub async fn get_referral_id(
     Ok(String::from_utf8_lossy(&validator_profile.referral_id).to_string())
 }
 
-/// Builds a ChainX runtime specific client.
-pub async fn build_client<U: Into<String>>(url: U) -> Result<ChainXClient> {
+/// Builds a ChainX runtime specific client connected to the first reachable url of `urls`.
+///
+/// The pallets hard-coded in the runtime are checked with `CompatCheck::Warn`.
+pub async fn build_client<I, U>(urls: I) -> Result<ChainXClient>
+where
+    I: IntoIterator<Item = U>,
+    U: AsRef<str>,
+{
+    let rpc = Rpc::new(urls).await?;
+    build_client_on(&rpc, CompatCheck::Warn).await
+}
+
+/// Builds a ChainX runtime specific client sharing the connection of `rpc`.
+///
+/// The pallets hard-coded in the runtime are checked against the metadata of the node
+/// before, according to `check`.
+pub async fn build_client_on(rpc: &Rpc, check: CompatCheck) -> Result<ChainXClient> {
+    compat::check_at_startup(rpc, check).await?;
     Ok(ClientBuilder::<ChainXRuntime>::new()
-        .set_url(url)
+        .set_client(rpc.connection().await)
         .build()
         .await?)
 }
@@ -81,3 +106,228 @@ pub async fn block_hash(
         Ok(None)
     }
 }
+
+/// The block to query at, given by the `--at` option.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BlockAt {
+    /// `1000`
+    Number(BlockNumber),
+    /// `0x...`
+    Hash(Hash),
+    /// `finalized`
+    Finalized,
+    /// `head~N`, the N-th ancestor of the best block, `head` is the same as `head~0`.
+    Head(BlockNumber),
+    /// `2021-01-01T14:00:00Z`, the last block produced at or before the time.
+    Time(DateTime<Utc>),
+}
+
+fn parse_time(s: &str) -> Option<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(s) {
+        return Some(time.with_timezone(&Utc));
+    }
+    // The time without the timezone is in UTC.
+    [
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(s, format).ok())
+    .or_else(|| {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            .ok()
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+    })
+    .map(|time| Utc.from_utc_datetime(&time))
+}
+
+impl FromStr for BlockAt {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("finalized") {
+            return Ok(Self::Finalized);
+        }
+        if s.eq_ignore_ascii_case("head") {
+            return Ok(Self::Head(0));
+        }
+        if let Some(offset) = s.strip_prefix("head~") {
+            return Ok(Self::Head(
+                offset
+                    .parse()
+                    .map_err(|err| anyhow!("Invalid block offset {}: {}", s, err))?,
+            ));
+        }
+        if let Some(hash) = s.strip_prefix("0x") {
+            let bytes = hex::decode(hash)?;
+            if bytes.len() != Hash::len_bytes() {
+                return Err(anyhow!("Invalid block hash {}: expected 32 bytes", s));
+            }
+            return Ok(Self::Hash(Hash::from_slice(&bytes)));
+        }
+        if !s.is_empty() && s.chars().all(|c| c.is_ascii_digit()) {
+            return Ok(Self::Number(s.parse()?));
+        }
+        parse_time(s).map(Self::Time).ok_or_else(|| {
+            anyhow!(
+                "Invalid block {}, expected a number, a 0x hash, `finalized`, `head~N` or a time",
+                s
+            )
+        })
+    }
+}
+
+async fn best_number(rpc: &Rpc) -> Result<BlockNumber> {
+    Ok(rpc
+        .header(None)
+        .await?
+        .ok_or_else(|| anyhow!("Header of the best block not found"))?
+        .number)
+}
+
+async fn block_hash_of(rpc: &Rpc, number: BlockNumber) -> Result<Hash> {
+    rpc.block_hash(number)
+        .await?
+        .ok_or_else(|| anyhow!("Block #{} not found", number))
+}
+
+/// Returns the value of `Timestamp::Now` in milliseconds at block `number`.
+async fn timestamp_at(rpc: &Rpc, number: BlockNumber) -> Result<u64> {
+    let hash = block_hash_of(rpc, number).await?;
+    let key = StorageKey(storage_prefix_for("Timestamp", "Now"));
+    Ok(rpc
+        .get_storage_value::<u64>(&key, Some(hash))
+        .await?
+        .unwrap_or_default())
+}
+
+impl BlockAt {
+    /// Returns the hash of the block.
+    pub async fn hash(&self, rpc: &Rpc) -> Result<Hash> {
+        match self {
+            Self::Number(number) => block_hash_of(rpc, *number).await,
+            Self::Hash(hash) => Ok(*hash),
+            Self::Finalized => rpc.finalized_head().await,
+            Self::Head(offset) => {
+                let best = best_number(rpc).await?;
+                let number = best.checked_sub(*offset).ok_or_else(|| {
+                    anyhow!(
+                        "head~{} is before the genesis, the best block is #{}",
+                        offset,
+                        best
+                    )
+                })?;
+                block_hash_of(rpc, number).await
+            }
+            Self::Time(time) => {
+                let target = time.timestamp_millis();
+                if target < 0 {
+                    return Err(anyhow!("Time {} is before the genesis", time));
+                }
+                let target = target as u64;
+
+                // Binary search the last block whose timestamp is not after the target,
+                // the genesis block has no timestamp.
+                let best = best_number(rpc).await?;
+                if best == 0 || timestamp_at(rpc, 1).await? > target {
+                    return Err(anyhow!("No block is produced at or before {}", time));
+                }
+                let (mut low, mut high) = (1, best);
+                while low < high {
+                    let mid = low + (high - low + 1) / 2;
+                    if timestamp_at(rpc, mid).await? <= target {
+                        low = mid;
+                    } else {
+                        high = mid - 1;
+                    }
+                }
+                block_hash_of(rpc, low).await
+            }
+        }
+    }
+}
+
+arg_enum! {
+  /// The block the queries default to and the extrinsics are waited for.
+  #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+  pub enum Finality {
+      Best,
+      Finalized,
+  }
+}
+
+impl Default for Finality {
+    fn default() -> Self {
+        Self::Best
+    }
+}
+
+/// Returns the hash of the block given by `--at`.
+///
+/// Defaults to the finalized head with `--finality finalized`, otherwise `None` for the best block.
+pub async fn block_hash_at(
+    rpc: &Rpc,
+    at: Option<&BlockAt>,
+    finality: Finality,
+) -> Result<Option<Hash>> {
+    match (at, finality) {
+        (Some(at), _) => Ok(Some(at.hash(rpc).await?)),
+        (None, Finality::Finalized) => Ok(Some(rpc.finalized_head().await?)),
+        (None, Finality::Best) => Ok(None),
+    }
+}
+
+/// The `--at` option of the queries.
+#[derive(Clone, Debug, Default, StructOpt)]
+pub struct AtBlock {
+    /// block number, 0x hash, `finalized`, `head~N` or time, e.g., `2021-01-01T14:00:00Z`
+    #[structopt(long, alias = "block-number")]
+    pub at: Option<BlockAt>,
+}
+
+impl AtBlock {
+    /// Returns the hash of the block to query at, see [`block_hash_at`].
+    pub async fn hash(&self, rpc: &Rpc, finality: Finality) -> Result<Option<Hash>> {
+        block_hash_at(rpc, self.at.as_ref(), finality).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_block_at() {
+        assert_eq!("100".parse::<BlockAt>().unwrap(), BlockAt::Number(100));
+        assert_eq!("finalized".parse::<BlockAt>().unwrap(), BlockAt::Finalized);
+        assert_eq!("head".parse::<BlockAt>().unwrap(), BlockAt::Head(0));
+        assert_eq!("head~10".parse::<BlockAt>().unwrap(), BlockAt::Head(10));
+        assert_eq!(
+            format!("0x{}", "11".repeat(32)).parse::<BlockAt>().unwrap(),
+            BlockAt::Hash(Hash::repeat_byte(0x11))
+        );
+        let time = Utc.from_utc_datetime(
+            &NaiveDate::from_ymd_opt(2021, 1, 1)
+                .and_then(|date| date.and_hms_opt(14, 0, 0))
+                .unwrap(),
+        );
+        assert_eq!(
+            "2021-01-01T14:00:00Z".parse::<BlockAt>().unwrap(),
+            BlockAt::Time(time)
+        );
+        assert_eq!(
+            "2021-01-01T22:00:00+08:00".parse::<BlockAt>().unwrap(),
+            BlockAt::Time(time)
+        );
+        assert_eq!(
+            "2021-01-01 14:00".parse::<BlockAt>().unwrap(),
+            BlockAt::Time(time)
+        );
+        assert!("0x1234".parse::<BlockAt>().is_err());
+        assert!("head~x".parse::<BlockAt>().is_err());
+        assert!("yesterday".parse::<BlockAt>().is_err());
+    }
+}